@@ -4,9 +4,58 @@
 
 const anchor = require("@coral-xyz/anchor");
 
+// Houses, campaigns and campaign players created before the account version byte
+// cannot be deserialized by the current program and have to be rewritten with
+// migrate_account. Houses go first, then campaigns, then the players of the
+// already migrated campaigns.
+const ACCOUNT_VERSION = 2;
+
 module.exports = async function (provider: any) {
   // Configure client to use the provider.
   anchor.setProvider(provider);
 
-  // Add your deploy script here.
+  const program = anchor.workspace.Clubhouse;
+  const connection = provider.connection;
+
+  const legacyAccounts = async (name: string) => {
+    const discriminator = program.idl.accounts.find((a: any) => a.name === name).discriminator;
+    const accounts = await connection.getProgramAccounts(program.programId, {
+      filters: [{ memcmp: { offset: 0, bytes: anchor.utils.bytes.bs58.encode(Buffer.from(discriminator)) } }],
+    });
+    return accounts.filter(({ account }: any) => {
+      try {
+        return program.coder.accounts.decode(name, account.data).version !== ACCOUNT_VERSION;
+      } catch {
+        return true;
+      }
+    });
+  };
+
+  const migrate = async (legacyAccount: any, campaign: any = null, gameDepositVault: any = null) => {
+    const signature = await program.methods
+      .migrateAccount()
+      .accountsPartial({ legacyAccount, campaign, gameDepositVault, payer: provider.wallet.publicKey })
+      .rpc();
+    console.log("migrated", legacyAccount.toBase58(), signature);
+  };
+
+  for (const { pubkey } of await legacyAccounts("House")) {
+    await migrate(pubkey);
+  }
+
+  for (const { pubkey } of await legacyAccounts("Campaign")) {
+    // stake campaigns read their escrowed total from the deposit vault
+    const [gameDepositVault] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("player_deposit"), pubkey.toBuffer()],
+      program.programId
+    );
+    const vaultExists = (await connection.getAccountInfo(gameDepositVault)) !== null;
+    await migrate(pubkey, null, vaultExists ? gameDepositVault : null);
+  }
+
+  for (const { pubkey, account } of await legacyAccounts("CampaignPlayer")) {
+    // discriminator, then the 33 byte player identity
+    const campaign = new anchor.web3.PublicKey(account.data.subarray(8 + 33, 8 + 65));
+    await migrate(pubkey, campaign);
+  }
 };
@@ -76,4 +76,54 @@ pub enum ErrorCodes {
     NoStake,
     #[msg("Manager token account required")]
     ManagerTokenAccountRequired,
-}
\ No newline at end of file
+    #[msg("Invalid game mode")]
+    InvalidGameMode,
+    #[msg("Account already uses the current layout")]
+    AccountAlreadyMigrated,
+    #[msg("Account must be migrated to the current layout first")]
+    AccountNotMigrated,
+    #[msg("Invalid energy configuration")]
+    InvalidEnergyConfig,
+    #[msg("Energy transfers are disabled")]
+    EnergyTransferDisabled,
+    #[msg("Daily energy transfer limit exceeded")]
+    EnergyTransferLimitExceeded,
+    #[msg("Campaign energy is not tokenized")]
+    EnergyNotTokenized,
+    #[msg("Asset is frozen")]
+    AssetFrozen,
+    #[msg("Asset is listed for sale")]
+    AssetListed,
+    #[msg("Core asset collection account is required")]
+    CoreCollectionRequired,
+    #[msg("Compressed NFT proof is invalid")]
+    CompressedProofInvalid,
+    #[msg("Token record required for programmable NFTs")]
+    TokenRecordRequired,
+    #[msg("Accounts required to lock the player NFT are missing")]
+    NftLockAccountsRequired,
+    #[msg("This identity cannot be locked")]
+    NftLockUnsupported,
+    #[msg("The campaign does not accept this kind of player NFT")]
+    IdentityTypeNotAllowed,
+    #[msg("The game can not be expired yet")]
+    GameNotExpired,
+    #[msg("Trait proof is invalid")]
+    TraitProofInvalid,
+    #[msg("Early unstaking is disabled")]
+    EarlyUnstakeDisabled,
+    #[msg("Stake yield must be claimed with the stake")]
+    StakeYieldUnclaimed,
+    #[msg("Stakers are owed yield, their stakes must be claimed first")]
+    StakeYieldOwed,
+    #[msg("Slashing is disabled")]
+    SlashingDisabled,
+    #[msg("Slash dispute window is still open")]
+    SlashDisputeOpen,
+    #[msg("Slash dispute window has closed")]
+    SlashDisputeClosed,
+    #[msg("Slashed stake is pending")]
+    SlashPending,
+    #[msg("Game price is above the accepted maximum")]
+    PriceAboveMax,
+}
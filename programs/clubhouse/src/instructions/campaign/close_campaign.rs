@@ -1,12 +1,18 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, metadata::MetadataAccount, token_interface::{Mint, TokenAccount, TokenInterface}};
 
-use crate::{errors::{self, ErrorCodes}, execute_token_close, execute_token_transfer, state::{metadata_is_collection, ManagerSlot}, Campaign, House, TokenUse};
+use crate::{errors::{self, ErrorCodes}, execute_lamport_transfer, execute_token_close, execute_token_transfer, state::ManagerSlot, Campaign, House, TokenUse, ACCOUNT_VERSION};
 
-pub fn close_campaign(ctx: Context<CloseCampaign>) -> Result<()> {
+pub fn close_campaign<'info>(ctx: Context<'_, '_, '_, 'info, CloseCampaign<'info>>) -> Result<()> {
     if false &&ctx.accounts.campaign.time_span.is_active(Clock::get()?.unix_timestamp) {
         return err!(errors::ErrorCodes::ActiveCampaign);
     }
+    require!(ctx.accounts.campaign.pending_slashes == 0, ErrorCodes::SlashPending);
+    // stake yield is paid from the reward vault, which is refunded to the creator and closed below
+    require!(
+        ctx.accounts.campaign.total_staked == 0 || ctx.accounts.campaign.token_config.map_or(true, |c| c.stake_yield.is_none()),
+        ErrorCodes::StakeYieldOwed
+    );
     ctx.accounts.house.remove_campaign();
 
     let campaign_key_bytes = ctx.accounts.campaign.key().to_bytes();
@@ -14,31 +20,74 @@ pub fn close_campaign(ctx: Context<CloseCampaign>) -> Result<()> {
 
     let binding = [&[campaign_key_bytes.as_ref(), &bump][..]];
     let seeds = Some(&binding[..]);
-    execute_token_transfer(
-        ctx.accounts.reward_vault.amount,
-        ctx.accounts.reward_vault.to_account_info(),
-        ctx.accounts.reward_withdrawal_account.to_account_info(),
-        ctx.accounts.campaign_auth.to_account_info(),
-        ctx.accounts.reward_token_program.to_account_info(),
-        seeds)?;
-    execute_token_close(
-        ctx.accounts.reward_vault.to_account_info(),
-        ctx.accounts.creator.to_account_info(),
-        ctx.accounts.campaign_auth.to_account_info(),
-        ctx.accounts.reward_token_program.to_account_info(),
-        seeds)?;
-
-    let is_paid = ctx.accounts.campaign.token_config.is_some_and(|c| c.token_use == TokenUse::Pay);
-
-    match (&ctx.accounts.game_deposit_vault, &ctx.accounts.game_mint, &ctx.accounts.deposit_withdrawal_account, &ctx.accounts.deposit_token_program, is_paid) {
-        (Some(game_deposit_vault), Some(_), Some(withdrawal_account), Some(token_program), true) => {
+    match (&ctx.accounts.reward_vault, &ctx.accounts.reward_mint, &ctx.accounts.reward_withdrawal_account, &ctx.accounts.reward_token_program, &ctx.accounts.sol_reward_vault) {
+        (Some(reward_vault), Some(reward_mint), Some(reward_withdrawal_account), Some(reward_token_program), None) => {
+            execute_token_transfer(
+                reward_vault.amount,
+                reward_mint.decimals,
+                reward_vault.to_account_info(),
+                reward_mint.to_account_info(),
+                reward_withdrawal_account.to_account_info(),
+                ctx.accounts.campaign_auth.to_account_info(),
+                reward_token_program.to_account_info(),
+                seeds,
+                ctx.remaining_accounts)?;
+            execute_token_close(
+                reward_vault.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.campaign_auth.to_account_info(),
+                reward_token_program.to_account_info(),
+                seeds)?;
+        },
+        (None, None, None, None, Some(sol_reward_vault)) if ctx.accounts.campaign.has_native_rewards() => {
+            execute_lamport_transfer(
+                sol_reward_vault.lamports(),
+                sol_reward_vault.to_account_info(),
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                Some(&[&[b"sol_rewards", campaign_key_bytes.as_ref(), &[ctx.accounts.campaign.sol_rewards_bump]]]))?;
+        },
+        (_, _, _, _, _) => return err!(errors::ErrorCodes::InvalidInput),
+    }
+
+    // outstanding stakes stay escrowed in the deposit vault under campaign_auth,
+    // claim_stake only needs the campaign key to return them
+    let native = ctx.accounts.campaign.token_config.is_some_and(|c| c.is_native());
+    let deposit_vault_passed = match native {
+        true => ctx.accounts.sol_deposit_vault.is_some(),
+        false => ctx.accounts.game_deposit_vault.is_some(),
+    };
+    let drain_deposits = match ctx.accounts.campaign.token_config.map(|c| c.token_use) {
+        Some(TokenUse::Pay) => true,
+        Some(TokenUse::Stake | TokenUse::StakeForEnergy) => ctx.accounts.campaign.total_staked == 0 && deposit_vault_passed,
+        _ => false,
+    };
+    if ctx.accounts.campaign.total_staked > 0 {
+        msg!("{} staked by {} players remains escrowed", ctx.accounts.campaign.total_staked, ctx.accounts.campaign.staker_count);
+    }
+
+    if native && drain_deposits {
+        let sol_deposit_vault = ctx.accounts.sol_deposit_vault.as_ref().ok_or(ErrorCodes::InvalidInput)?;
+        execute_lamport_transfer(
+            sol_deposit_vault.lamports(),
+            sol_deposit_vault.to_account_info(),
+            ctx.accounts.creator.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            Some(&[&[b"sol_deposit", campaign_key_bytes.as_ref(), &[ctx.accounts.campaign.sol_deposit_bump]]]))?;
+    }
+
+    match (&ctx.accounts.game_deposit_vault, &ctx.accounts.game_mint, &ctx.accounts.deposit_withdrawal_account, &ctx.accounts.deposit_token_program, drain_deposits && !native) {
+        (Some(game_deposit_vault), Some(game_mint), Some(withdrawal_account), Some(token_program), true) => {
             execute_token_transfer(
                 game_deposit_vault.amount,
+                game_mint.decimals,
                 game_deposit_vault.to_account_info(),
+                game_mint.to_account_info(),
                 withdrawal_account.to_account_info(),
                 ctx.accounts.campaign_auth.to_account_info(),
             token_program.to_account_info(),
-            seeds)?;
+            seeds,
+            ctx.remaining_accounts)?;
             execute_token_close(
                 game_deposit_vault.to_account_info(),
                 ctx.accounts.creator.to_account_info(),
@@ -58,7 +107,7 @@ pub fn close_campaign(ctx: Context<CloseCampaign>) -> Result<()> {
 
 #[derive(Accounts)]
 pub struct CloseCampaign<'info> {
-    #[account(mut, close=creator, has_one=creator, has_one=house)]
+    #[account(mut, close=creator, has_one=creator, has_one=house, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub campaign: Box<Account<'info, Campaign>>,
     /// CHECK: the campaign auth PDA
     #[account()]
@@ -69,17 +118,21 @@ pub struct CloseCampaign<'info> {
         associated_token::authority=creator, 
         associated_token::mint = reward_mint, 
         associated_token::token_program = reward_token_program)]
-    pub reward_withdrawal_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub reward_withdrawal_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     /// the vault where the rewards are held to be claimed
 
-    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = campaign.reward_mint @ ErrorCodes::InvalidInput)]
+    pub reward_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
     #[account(
         mut,
         seeds=[b"rewards", campaign.key().as_ref()], 
         bump
     )]
-    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
-    #[account(mut)]
+    pub reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// holds the rewards of campaigns paying in lamports, replaces the reward token accounts
+    #[account(mut, seeds=[b"sol_rewards", campaign.key().as_ref()], bump)]
+    pub sol_reward_vault: Option<SystemAccount<'info>>,
+    #[account(mut, constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub house: Box<Account<'info, House>>,
     #[account(mut)]
     pub creator: Signer<'info>,
@@ -98,10 +151,14 @@ pub struct CloseCampaign<'info> {
     
     pub game_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
 
+    /// lamport deposits of campaigns priced in SOL
+    #[account(mut, seeds=[b"sol_deposit", campaign.key().as_ref()], bump)]
+    pub sol_deposit_vault: Option<SystemAccount<'info>>,
+
     pub deposit_token_program: Option<Interface<'info, TokenInterface>>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub reward_token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
 
 
@@ -114,10 +171,22 @@ pub struct CloseCampaign<'info> {
     #[account(
         mut,
         close=creator,
-        seeds=[b"manager_slot", house.key().as_ref(), manager_nft_metadata.as_ref().unwrap().mint.key().as_ref()], bump,
+        seeds=[b"manager_slot", house.key().as_ref(), &match (&manager_nft_metadata, &manager_core_nft, &manager_member_mint) {
+            (Some(metadata), _, _) => metadata.mint.to_bytes(),
+            (None, Some(core_nft), _) => core_nft.key().to_bytes(),
+            (None, None, Some(member_mint)) => member_mint.key().to_bytes(),
+            (None, None, None) => Pubkey::default().to_bytes(),
+        }[..]], bump,
         constraint = house.manager_collection.is_some() @ ErrorCodes::InvalidInput,
-        constraint = metadata_is_collection(&manager_nft_metadata.as_ref().unwrap(),&house.manager_collection.unwrap()).is_ok() @ ErrorCodes::CollectionProofInvalid
-
+        constraint = manager_slot.campaign == campaign.key() @ ErrorCodes::CollectionProofInvalid,
     )]
     pub manager_slot: Option<Account<'info,ManagerSlot>>,
+
+    /// CHECK: only used to derive the manager slot, which is bound to the campaign
+    #[account()]
+    pub manager_core_nft: Option<AccountInfo<'info>>,
+
+    /// CHECK: only used to derive the manager slot, which is bound to the campaign
+    #[account()]
+    pub manager_member_mint: Option<AccountInfo<'info>>,
 }
\ No newline at end of file
@@ -1,10 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{token_interface::TokenInterface, token_interface::{Mint, TokenAccount}, metadata::MetadataAccount};
 
-use crate::{errors::{self, ErrorCodes}, execute_token_transfer, metadata_is_collection, state::{ManagerSlot, PlayerIdentity, SimplifiedAssetV1, UpdateAuthority}, string_len_borsh, string_option_len, validate_string, Campaign, House, NftCampaignConfig, TimeSpan, TokenCampaignConfig, TokenUse};
+use crate::{errors::{self, ErrorCodes}, execute_lamport_transfer, execute_token_transfer, get_amount_after_transfer_fee, metadata_is_collection, state::{load_core_asset, verify_group_member, IdentityType, ManagerSlot, PlayerIdentity}, string_len_borsh, string_option_len, validate_string, vec_len_borsh, Campaign, ACCOUNT_VERSION, CollectionEntry, GameMode, House, NftCampaignConfig, PenaltyDestination, StakeTier, TimeSpan, TokenCampaignConfig, TokenUse, TraitMultiplier};
 
 
-pub fn create_campaign(ctx: Context<CreateCampaign>,
+pub fn create_campaign<'info>(ctx: Context<'_, '_, '_, 'info, CreateCampaign<'info>>,
     campaign_name: String,
     uri: Option<String>,
     fund_amount: u64, 
@@ -13,7 +13,11 @@ pub fn create_campaign(ctx: Context<CreateCampaign>,
     time_span: TimeSpan,
     nft_campaign_config: Option<NftCampaignConfig>, 
     token_campaign_config: Option<TokenCampaignConfig>,
-    burn_remainder: bool) -> Result<()> {
+    burn_remainder: bool,
+    game_modes: Vec<GameMode>,
+    collections: Vec<CollectionEntry>,
+    trait_multipliers: Vec<TraitMultiplier>,
+    stake_tiers: Vec<StakeTier>) -> Result<()> {
     validate_string(&campaign_name)?;
     let clock = Clock::get()?;
     let ts_now = clock.unix_timestamp;
@@ -27,24 +31,50 @@ pub fn create_campaign(ctx: Context<CreateCampaign>,
         return err!(errors::ErrorCodes::InvalidTimeSpan);
     }
 
+    require!(game_modes.len() <= Campaign::MAX_GAME_MODES, ErrorCodes::InvalidGameMode);
+    require!(collections.len() <= Campaign::MAX_COLLECTIONS, ErrorCodes::InvalidInput);
+    require!(collections.is_empty() || nft_campaign_config.is_some(), ErrorCodes::InvalidInput);
+    require!(collections.iter().all(|c| c.reward_multiplier_bps > 0), ErrorCodes::InvalidInput);
+    require!(trait_multipliers.len() <= Campaign::MAX_TRAIT_MULTIPLIERS, ErrorCodes::InvalidInput);
+    require!(trait_multipliers.is_empty() || nft_campaign_config.is_some(), ErrorCodes::InvalidInput);
+    for multiplier in &trait_multipliers {
+        multiplier.validate()?;
+    }
+    StakeTier::validate_tiers(&stake_tiers)?;
+    require!(
+        stake_tiers.is_empty() != token_campaign_config.is_some_and(|c| c.token_use == TokenUse::StakeForEnergy),
+        ErrorCodes::InvalidInput
+    );
+
+    if let Some(nft_config) = nft_campaign_config {
+        nft_config.validate()?;
+        if nft_config.use_house_energy {
+            require!(ctx.accounts.house.player_energy.is_some(), ErrorCodes::InvalidEnergyConfig);
+        }
+    }
+    require!(
+        nft_campaign_config.is_some_and(|c| c.tokenized_energy) == ctx.accounts.energy_mint.is_some(),
+        ErrorCodes::InvalidEnergyConfig
+    );
+
+    match (&ctx.accounts.reward_mint, &ctx.accounts.reward_depositor_account, &ctx.accounts.reward_vault, &ctx.accounts.sol_reward_vault) {
+        (Some(_), Some(_), Some(_), None) => {},
+        (None, None, None, Some(_)) => {
+            // lamports cannot be burned and stake yield is paid from the rewards token vault
+            require!(!burn_remainder, ErrorCodes::InvalidInput);
+            require!(token_campaign_config.map_or(true, |c| c.stake_yield.is_none()), ErrorCodes::InvalidInput);
+        },
+        (_, _, _, _) => return err!(ErrorCodes::InvalidInput),
+    }
+
     // Validate manager NFT if provided
     let mut signer_must_pay = true;
     if ctx.accounts.signer.key() != ctx.accounts.house.house_admin || ctx.accounts.manager_slot.is_some() {
-        if ctx.accounts.house.manager_collection.is_some() && ctx.accounts.manager_nft_token_account.is_some() {
-
-            
-                require!(ctx.accounts.manager_nft_token_account.is_some(), ErrorCodes::ManagerTokenAccountRequired);
-                require!(ctx.accounts.manager_nft_metadata.is_some(), ErrorCodes::MissingMetadata);
-                
-                let token_account = ctx.accounts.manager_nft_token_account.as_ref().unwrap();
-                require!(token_account.owner == ctx.accounts.signer.key(), ErrorCodes::TokenOwnerMismatch);
-                require!(token_account.amount == 1, ErrorCodes::OwnerBalanceMismatch);
-                
-                let metadata = ctx.accounts.manager_nft_metadata.as_ref().unwrap();
-                require!(metadata_is_collection(metadata, &ctx.accounts.house.manager_collection.unwrap()).is_ok(), ErrorCodes::CollectionProofInvalid);
+        if ctx.accounts.house.manager_collection.is_some() && (ctx.accounts.manager_nft_token_account.is_some() || ctx.accounts.manager_core_nft.is_some()) {
+                let manager = ctx.accounts.validate_manager_nft()?;
                 require!(ctx.accounts.manager_slot.is_some(), ErrorCodes::InvalidInput);
                 signer_must_pay = false;
-                ctx.accounts.manager_slot.as_mut().unwrap().manager = ctx.accounts.manager_nft_metadata.as_ref().unwrap().mint;
+                ctx.accounts.manager_slot.as_mut().unwrap().manager = manager.pubkey;
                 ctx.accounts.manager_slot.as_mut().unwrap().campaign = ctx.accounts.campaign.key();
                 ctx.accounts.manager_slot.as_mut().unwrap().house = ctx.accounts.house.key();
                 ctx.accounts.manager_slot.as_mut().unwrap().exit(&crate::id())?;
@@ -54,6 +84,8 @@ pub fn create_campaign(ctx: Context<CreateCampaign>,
             else {
                 require!(ctx.accounts.manager_nft_token_account.is_none(), ErrorCodes::InvalidInput);
                 require!(ctx.accounts.manager_nft_metadata.is_none(), ErrorCodes::InvalidInput);
+                require!(ctx.accounts.manager_core_nft.is_none(), ErrorCodes::InvalidInput);
+                require!(ctx.accounts.manager_member_mint.is_none(), ErrorCodes::InvalidInput);
                 require!(ctx.accounts.manager_slot.is_none(), ErrorCodes::InvalidInput);
                 require!(ctx.accounts.creation_fee_account.is_some(), ErrorCodes::InvalidInput);
             }
@@ -64,7 +96,24 @@ pub fn create_campaign(ctx: Context<CreateCampaign>,
     
 
     match token_campaign_config {
+        Some(token_config) if token_config.is_native() => {
+            token_config.validate()?;
+            require!(ctx.accounts.game_mint.is_none() && ctx.accounts.game_deposit_vault.is_none(), ErrorCodes::InvalidInput);
+            let sol_deposit_vault = ctx.accounts.sol_deposit_vault.as_ref().ok_or(ErrorCodes::InvalidInput)?;
+            // the PDA only holds lamports, it must stay rent exempt between deposits
+            let rent = Rent::get()?.minimum_balance(0).saturating_sub(sol_deposit_vault.lamports());
+            if rent > 0 {
+                execute_lamport_transfer(
+                    rent,
+                    ctx.accounts.signer.to_account_info(),
+                    sol_deposit_vault.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    None)?;
+            }
+        },
         Some(token_config) => {
+            token_config.validate()?;
+            require!(ctx.accounts.sol_deposit_vault.is_none(), ErrorCodes::InvalidInput);
             
             match (token_config.token_use, &ctx.accounts.game_mint, &ctx.accounts.game_deposit_vault) {
                 (TokenUse::Burn, Some(_), None) => {},
@@ -77,6 +126,19 @@ pub fn create_campaign(ctx: Context<CreateCampaign>,
             if token_config.spending_mint_decimals != ctx.accounts.game_mint.as_ref().unwrap().decimals {
                 return err!(ErrorCodes::InvalidInput)
             }
+            if let Some(revenue_split) = token_config.revenue_split {
+                revenue_split.validate(&ctx.accounts.house.config)?;
+                // shares are paid in the spending mint, so the receiving vaults must hold it too
+                if revenue_split.reward_pool_bps > 0 {
+                    require!(ctx.accounts.reward_mint.as_ref().is_some_and(|m| m.key() == token_config.spending_mint), ErrorCodes::InvalidInput);
+                }
+                if revenue_split.house_bps(&ctx.accounts.house.config) > 0 {
+                    require!(token_config.spending_mint == ctx.accounts.house.house_currency, ErrorCodes::InvalidInput);
+                }
+            }
+            if token_config.early_unstake.is_some_and(|e| e.penalty_destination == PenaltyDestination::RewardPool) {
+                require!(ctx.accounts.reward_mint.as_ref().is_some_and(|m| m.key() == token_config.spending_mint), ErrorCodes::InvalidInput);
+            }
         },
         None => {
         }
@@ -85,11 +147,12 @@ pub fn create_campaign(ctx: Context<CreateCampaign>,
 
     let campaign = &mut ctx.accounts.campaign;
     campaign.auth_bump = ctx.bumps.campaign_auth;
-    campaign.reward_mint = ctx.accounts.reward_mint.key();
-    campaign.reward_mint_decimals = ctx.accounts.reward_mint.decimals;
+    campaign.reward_mint = ctx.accounts.reward_mint.as_ref().map_or(System::id(), |m| m.key());
+    campaign.reward_mint_decimals = ctx.accounts.reward_mint.as_ref().map_or(TokenCampaignConfig::NATIVE_DECIMALS, |m| m.decimals);
     campaign.house = ctx.accounts.house.key();
     campaign.campaign_name = campaign_name;
     campaign.uri = uri;
+    campaign.version = ACCOUNT_VERSION;
     campaign.house_config_snapshot = ctx.accounts.house.config.clone();
     campaign.nft_config = nft_campaign_config;
     campaign.token_config = token_campaign_config;
@@ -97,20 +160,50 @@ pub fn create_campaign(ctx: Context<CreateCampaign>,
     campaign.creator = ctx.accounts.signer.key();
     campaign.max_rewards_per_game = max_rewards_per_game;
     campaign.rewards_claim_fee = player_claim_price;
-    campaign.rewards_available = fund_amount;
-    campaign.manager_identity = match ctx.accounts.manager_nft_metadata.as_ref() {
-        Some(metadata) => PlayerIdentity{identity_type: crate::state::IdentityType::Nft, pubkey: metadata.mint.key()},
-        None => PlayerIdentity{identity_type: crate::state::IdentityType::User, pubkey: ctx.accounts.signer.key()},
+    campaign.rewards_available = match &ctx.accounts.reward_mint {
+        Some(reward_mint) => get_amount_after_transfer_fee(&reward_mint.to_account_info(), fund_amount)?,
+        None => fund_amount,
+    };
+    campaign.manager_identity = match (ctx.accounts.manager_nft_metadata.as_ref(), ctx.accounts.manager_core_nft.as_ref(), ctx.accounts.manager_member_mint.as_ref()) {
+        (Some(metadata), _, _) => PlayerIdentity{identity_type: crate::state::IdentityType::Nft, pubkey: metadata.mint.key()},
+        (None, Some(core_nft), _) => PlayerIdentity{identity_type: crate::state::IdentityType::MplCore, pubkey: core_nft.key()},
+        (None, None, Some(member_mint)) => PlayerIdentity{identity_type: crate::state::IdentityType::Token2022Nft, pubkey: member_mint.key()},
+        (None, None, None) => PlayerIdentity{identity_type: crate::state::IdentityType::User, pubkey: ctx.accounts.signer.key()},
     };
     campaign.player_count = 0;
     campaign.active_games = 0;
     campaign.total_games = 0;
     campaign.unclaimed_sol_fees = 0;
-    campaign._reserved_config = [0; 7];
-    campaign._reserved_for_token = [0; 2];
-    campaign._reserved_bytes = [0; 7];
+    campaign._reserved_config = [0; 1];
+    campaign.creator_revenue = 0;
+    campaign.pending_slashes = 0;
+    // the yield allocation is carved out of the pool so games can never reserve it
+    campaign.stake_yield_remaining = match token_campaign_config.and_then(|c| c.stake_yield) {
+        Some(stake_yield) => {
+            campaign.rewards_available = campaign.rewards_available.checked_sub(stake_yield.allocation).ok_or(ErrorCodes::InsufficientFunds)?;
+            stake_yield.allocation
+        },
+        None => 0,
+    };
+    campaign.yield_per_stake = 0;
+    campaign.yield_updated_at = ts_now.max(time_span.start_time);
+    campaign.total_staked = 0;
+    campaign.staker_count = 0;
+    campaign._reserved_for_token = [0; 4];
+    campaign.sol_deposit_bump = ctx.bumps.sol_deposit_vault.unwrap_or(0);
+    campaign.sol_rewards_bump = ctx.bumps.sol_reward_vault.unwrap_or(0);
+    campaign._reserved_bytes = [0; 5];
     campaign.reserved_rewards = 0;
     campaign.burn_remainder = burn_remainder;
+    campaign.game_modes = game_modes;
+    campaign.collections = collections;
+    campaign.trait_multipliers = trait_multipliers;
+    campaign.stake_tiers = stake_tiers;
+    for index in 0..campaign.collections.len() {
+        if let Some(collection_config) = campaign.player_nft_config(index as u8)? {
+            collection_config.validate()?;
+        }
+    }
 
     ctx.accounts.house.add_campaign();
     let fee = match signer_must_pay {
@@ -118,25 +211,48 @@ pub fn create_campaign(ctx: Context<CreateCampaign>,
         false => 0
     };
     
-    ctx.accounts.house.unclaimed_house_fees += fee;
     if fee > 0 {
+        let house_currency_mint = ctx.accounts.house_currency_mint.as_ref().ok_or(ErrorCodes::InvalidInput)?;
+        let house_token_program = ctx.accounts.house_token_program.as_ref().ok_or(ErrorCodes::InvalidInput)?;
         execute_token_transfer(
             fee, 
+            ctx.accounts.house.house_currency_decimals,
             ctx.accounts.creation_fee_account.as_ref().unwrap().to_account_info(), 
+            house_currency_mint.to_account_info(),
             ctx.accounts.house_vault.to_account_info(), 
             ctx.accounts.signer.to_account_info(), 
-            ctx.accounts.token_program.to_account_info(),
-            None)?;
+            house_token_program.to_account_info(),
+            None,
+            ctx.remaining_accounts)?;
+        ctx.accounts.house.unclaimed_house_fees += get_amount_after_transfer_fee(&house_currency_mint.to_account_info(), fee)?;
     }
 
-    if fund_amount > 0 {
-        execute_token_transfer(
-            fund_amount, 
-            ctx.accounts.reward_depositor_account.to_account_info(), 
-            ctx.accounts.reward_vault.to_account_info(), 
-            ctx.accounts.signer.to_account_info(), 
-            ctx.accounts.token_program.to_account_info(),
-            None)?;
+    match (&ctx.accounts.reward_mint, &ctx.accounts.reward_depositor_account, &ctx.accounts.reward_vault, &ctx.accounts.sol_reward_vault) {
+        (Some(reward_mint), Some(reward_depositor_account), Some(reward_vault), None) => {
+            if fund_amount > 0 {
+                execute_token_transfer(
+                    fund_amount, 
+                    reward_mint.decimals,
+                    reward_depositor_account.to_account_info(), 
+                    reward_mint.to_account_info(),
+                    reward_vault.to_account_info(), 
+                    ctx.accounts.signer.to_account_info(), 
+                    ctx.accounts.token_program.to_account_info(),
+                    None,
+                    ctx.remaining_accounts)?;
+            }
+        },
+        (None, None, None, Some(sol_reward_vault)) => {
+            // rent exemption is funded on top so payouts can spend all of rewards_available
+            let rent = Rent::get()?.minimum_balance(0).saturating_sub(sol_reward_vault.lamports());
+            execute_lamport_transfer(
+                fund_amount + rent,
+                ctx.accounts.signer.to_account_info(),
+                sol_reward_vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                None)?;
+        },
+        (_, _, _, _) => return err!(ErrorCodes::InvalidInput),
     }
 
     Ok(())
@@ -146,34 +262,53 @@ pub fn create_campaign(ctx: Context<CreateCampaign>,
 #[derive(Accounts)]
 #[instruction(
     campaign_name: String, 
-    uri: Option<String>,)]
+    uri: Option<String>,
+    fund_amount: u64,
+    max_rewards_per_game: u64,
+    player_claim_price: u64,
+    time_span: TimeSpan,
+    nft_campaign_config: Option<NftCampaignConfig>,
+    token_campaign_config: Option<TokenCampaignConfig>,
+    burn_remainder: bool,
+    game_modes: Vec<GameMode>,
+    collections: Vec<CollectionEntry>,
+    trait_multipliers: Vec<TraitMultiplier>,
+    stake_tiers: Vec<StakeTier>,)]
 pub struct CreateCampaign<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
 
-    #[account(init, payer=signer, space=8+472+string_len_borsh(&campaign_name)+string_option_len(&uri))]
+    #[account(init, payer=signer, space=8+Campaign::FIXED_SPACE+vec_len_borsh(game_modes.len(), GameMode::INIT_SPACE)+vec_len_borsh(collections.len(), CollectionEntry::INIT_SPACE)+vec_len_borsh(trait_multipliers.len(), TraitMultiplier::INIT_SPACE)+vec_len_borsh(stake_tiers.len(), StakeTier::INIT_SPACE)+string_len_borsh(&campaign_name)+string_option_len(&uri))]
     pub campaign: Account<'info, Campaign>,
 
     /// CHECK: campaign proxy signer
     #[account(seeds=[campaign.key().as_ref()], bump)]
     pub campaign_auth: AccountInfo<'info>,
 
-    #[account(mut)]
+    #[account(mut, constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub house: Box<Account<'info, House>>,
 
     /// pays the campaign creation fees
     #[account(mut)]
     pub creation_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// omitted for campaigns paying rewards in lamports
+    pub reward_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
 
     /// the vault where we pay the campaign creation fees
     #[account(mut,seeds=[b"vault",house.key().as_ref()], bump)]
     pub house_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// required when a creation fee is charged
+    #[account(address = house.house_currency @ ErrorCodes::InvalidInput)]
+    pub house_currency_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// token program of the house currency, which may differ from the reward mint's
+    pub house_token_program: Option<Interface<'info, TokenInterface>>,
+
     /// the account that deposits rewards for the campaign
     #[account(mut)]
-    pub reward_depositor_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub reward_depositor_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     /// the vault where the rewards are held to be claimed
 
     #[account(
@@ -184,7 +319,11 @@ pub struct CreateCampaign<'info> {
         token::mint = reward_mint, 
         token::authority = campaign_auth,
     )]
-    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// holds the rewards of campaigns paying in lamports, replaces the reward token accounts
+    #[account(mut, seeds=[b"sol_rewards", campaign.key().as_ref()], bump)]
+    pub sol_reward_vault: Option<SystemAccount<'info>>,
 
     pub game_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
     #[account(
@@ -197,6 +336,22 @@ pub struct CreateCampaign<'info> {
     )]
     pub game_deposit_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
+    /// lamport deposits of campaigns priced in SOL
+    #[account(mut, seeds=[b"sol_deposit", campaign.key().as_ref()], bump)]
+    pub sol_deposit_vault: Option<SystemAccount<'info>>,
+
+    /// mint for tokenized energy, authority is the campaign proxy signer
+    #[account(
+        init,
+        payer=signer,
+        seeds=[b"energy_mint", campaign.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = campaign_auth,
+        mint::token_program = token_program,
+    )]
+    pub energy_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
     pub token_program: Interface<'info, TokenInterface>,
 
     pub system_program: Program<'info, System>,
@@ -211,37 +366,57 @@ pub struct CreateCampaign<'info> {
         init,
         space= 8+32+32+32,
         payer=signer,
-        seeds=[b"manager_slot", house.key().as_ref(), manager_nft_metadata.as_ref().unwrap().mint.key().as_ref()], bump,
+        seeds=[b"manager_slot", house.key().as_ref(), &match (&manager_nft_metadata, &manager_core_nft, &manager_member_mint) {
+            (Some(metadata), _, _) => metadata.mint.to_bytes(),
+            (None, Some(core_nft), _) => core_nft.key().to_bytes(),
+            (None, None, Some(member_mint)) => member_mint.key().to_bytes(),
+            (None, None, None) => Pubkey::default().to_bytes(),
+        }[..]], bump,
         constraint = house.manager_collection.is_some() @ ErrorCodes::InvalidInput,
-        constraint = metadata_is_collection(&manager_nft_metadata.as_ref().unwrap(),&house.manager_collection.unwrap()).is_ok() @ ErrorCodes::CollectionProofInvalid
-        
     )]
     pub manager_slot: Option<Account<'info,ManagerSlot>>,
 
+    /// CHECK: Custom validation for mpl-core asset
+    #[account()]
+    pub manager_core_nft: Option<AccountInfo<'info>>,
+
+    /// CHECK: Custom validation for mpl-core collection, needed when the manager collection is its update authority or delegate
+    #[account()]
+    pub manager_core_collection: Option<AccountInfo<'info>>,
 
-    //// CHECK: Custom validation for mpl-core asset
-    //#[account()]
-    //pub manager_core_nft: Option<AccountInfo<'info>>,
+    /// CHECK: Token-2022 manager pass mint, validated through its group member extension
+    #[account()]
+    pub manager_member_mint: Option<AccountInfo<'info>>,
 }
 
-//impl CreateCampaign<'_> {
-//    pub fn validate_core_nft(&self) -> Result<()> {
-//        if let Some(core_nft_info) = &self.manager_core_nft {
-//            let nft = SimplifiedAssetV1::from_account_info(core_nft_info)?;
-//            
-//            if nft.owner != self.signer.key() {
-//                return err!(ErrorCodes::TokenOwnerMismatch);
-//            }
-//            
-//            if let Some(house_manager_collection) = self.house.manager_collection {
-//                if nft.update_authority != UpdateAuthority::Collection(house_manager_collection) {
-//                    return err!(ErrorCodes::OwnerBalanceMismatch);
-//                }
-//            } else {
-//                return err!(ErrorCodes::InvalidInput);
-//            }
-//        }
-//        
-//        Ok(())
-//    }
-//}
\ No newline at end of file
+impl CreateCampaign<'_> {
+    /// Verifies the signer holds a manager pass of the house manager collection
+    pub fn validate_manager_nft(&self) -> Result<PlayerIdentity> {
+        let manager_collection = self.house.manager_collection.ok_or(ErrorCodes::InvalidInput)?;
+        match (&self.manager_nft_token_account, &self.manager_nft_metadata, &self.manager_core_nft) {
+            (Some(token_account), None, None) if self.manager_member_mint.is_some() => {
+                let member_mint = self.manager_member_mint.as_ref().unwrap();
+                require!(token_account.owner == self.signer.key(), ErrorCodes::TokenOwnerMismatch);
+                require!(token_account.amount == 1, ErrorCodes::OwnerBalanceMismatch);
+                require!(token_account.mint == member_mint.key(), ErrorCodes::MetadataMismatch);
+                verify_group_member(member_mint, &manager_collection)?;
+                Ok(PlayerIdentity{identity_type: IdentityType::Token2022Nft, pubkey: member_mint.key()})
+            },
+            (Some(token_account), Some(metadata), None) => {
+                require!(token_account.owner == self.signer.key(), ErrorCodes::TokenOwnerMismatch);
+                require!(token_account.amount == 1, ErrorCodes::OwnerBalanceMismatch);
+                require!(token_account.mint == metadata.mint, ErrorCodes::MetadataMismatch);
+                require!(metadata_is_collection(metadata, &manager_collection, self.house.manager_collection_verification).is_ok(), ErrorCodes::CollectionProofInvalid);
+                Ok(PlayerIdentity{identity_type: IdentityType::Nft, pubkey: metadata.mint})
+            },
+            (None, None, Some(core_nft_info)) => {
+                let (nft, collection) = load_core_asset(core_nft_info, self.manager_core_collection.as_ref(), &self.signer.key(), &manager_collection)?;
+                nft.ensure_unlocked(collection.as_ref())?;
+                Ok(PlayerIdentity{identity_type: IdentityType::MplCore, pubkey: core_nft_info.key()})
+            },
+            (None, Some(_), None) => err!(ErrorCodes::ManagerTokenAccountRequired),
+            (Some(_), None, None) => err!(ErrorCodes::MissingMetadata),
+            (_, _, _) => err!(ErrorCodes::InvalidInput),
+        }
+    }
+}
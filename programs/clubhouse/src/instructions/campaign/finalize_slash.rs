@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{errors::ErrorCodes, execute_token_burn, execute_token_transfer, get_amount_after_transfer_fee, state::StakeSlash, Campaign, PenaltyDestination, ACCOUNT_VERSION};
+
+pub fn finalize_slash<'info>(ctx: Context<'_, '_, '_, 'info, FinalizeSlash<'info>>) -> Result<()> {
+    let now_ts = Clock::get()?.unix_timestamp;
+    let stake_slash = &ctx.accounts.stake_slash;
+    require!(now_ts > stake_slash.dispute_ends_at, ErrorCodes::SlashDisputeOpen);
+
+    let campaign_key = ctx.accounts.campaign.key();
+    let seeds: &[&[&[u8]]] = &[&[campaign_key.as_ref(), &[ctx.accounts.campaign.auth_bump]]];
+    match (stake_slash.destination, &ctx.accounts.reward_vault) {
+        (PenaltyDestination::Burn, None) => {
+            execute_token_burn(
+                stake_slash.amount,
+                ctx.accounts.game_deposit_mint.to_account_info(),
+                ctx.accounts.game_deposit_vault.to_account_info(),
+                ctx.accounts.campaign_auth.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                Some(seeds))?;
+        },
+        (PenaltyDestination::RewardPool, Some(reward_vault)) => {
+            execute_token_transfer(
+                stake_slash.amount,
+                ctx.accounts.game_deposit_mint.decimals,
+                ctx.accounts.game_deposit_vault.to_account_info(),
+                ctx.accounts.game_deposit_mint.to_account_info(),
+                reward_vault.to_account_info(),
+                ctx.accounts.campaign_auth.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                Some(seeds),
+                ctx.remaining_accounts)?;
+            let amount_received = get_amount_after_transfer_fee(&ctx.accounts.game_deposit_mint.to_account_info(), stake_slash.amount)?;
+            ctx.accounts.campaign.rewards_available = ctx.accounts.campaign.rewards_available.checked_add(amount_received).ok_or(ErrorCodes::InvalidInput)?;
+        },
+        (_, _) => return err!(ErrorCodes::InvalidInput),
+    }
+
+    ctx.accounts.campaign.pending_slashes = ctx.accounts.campaign.pending_slashes.saturating_sub(stake_slash.amount);
+    msg!("slash finalized: {}", stake_slash.amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FinalizeSlash<'info> {
+    #[account(mut, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+    #[account(mut, close=authority, has_one=campaign, has_one=authority,
+        seeds=[b"slash", stake_slash.campaign_player.as_ref()], bump)]
+    pub stake_slash: Box<Account<'info, StakeSlash>>,
+    /// CHECK: receives the slash rent, checked against stake_slash
+    #[account(mut)]
+    pub authority: AccountInfo<'info>,
+    /// CHECK: the campaign auth PDA
+    #[account(seeds=[campaign.key().as_ref()], bump)]
+    pub campaign_auth: AccountInfo<'info>,
+
+    #[account(mut, token::mint=game_deposit_mint, seeds=[b"player_deposit", campaign.key().as_ref()], bump)]
+    pub game_deposit_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, constraint = campaign.token_config.is_some_and(|c| c.spending_mint == game_deposit_mint.key()) @ ErrorCodes::InvalidInput)]
+    pub game_deposit_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// the campaign reward vault, only when the slash goes to the reward pool
+    #[account(
+        mut,
+        token::mint = game_deposit_mint,
+        seeds=[b"rewards", campaign.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{errors::ErrorCodes, execute_lamport_transfer, execute_token_transfer, get_amount_after_transfer_fee, Campaign, ACCOUNT_VERSION};
+
+pub fn fund_campaign<'info>(ctx: Context<'_, '_, '_, 'info, FundCampaign<'info>>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCodes::InvalidInput);
+    require!(!ctx.accounts.campaign.time_span.is_expired(Clock::get()?.unix_timestamp), ErrorCodes::CampaignExpired);
+
+    let amount_received = match (&ctx.accounts.reward_mint, &ctx.accounts.reward_vault, &ctx.accounts.funder_token_account, &ctx.accounts.token_program, &ctx.accounts.sol_reward_vault) {
+        (Some(reward_mint), Some(reward_vault), Some(funder_token_account), Some(token_program), None) => {
+            execute_token_transfer(
+                amount,
+                reward_mint.decimals,
+                funder_token_account.to_account_info(),
+                reward_mint.to_account_info(),
+                reward_vault.to_account_info(),
+                ctx.accounts.funder.to_account_info(),
+                token_program.to_account_info(),
+                None,
+                ctx.remaining_accounts)?;
+            get_amount_after_transfer_fee(&reward_mint.to_account_info(), amount)?
+        },
+        (None, None, None, None, Some(sol_reward_vault)) if ctx.accounts.campaign.has_native_rewards() => {
+            execute_lamport_transfer(
+                amount,
+                ctx.accounts.funder.to_account_info(),
+                sol_reward_vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                None)?;
+            amount
+        },
+        (_, _, _, _, _) => return err!(ErrorCodes::InvalidInput),
+    };
+    ctx.accounts.campaign.rewards_available = ctx.accounts.campaign.rewards_available.checked_add(amount_received).ok_or(ErrorCodes::InvalidInput)?;
+    msg!("campaign funded: {}", amount_received);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FundCampaign<'info> {
+    #[account(mut, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(address = campaign.reward_mint @ ErrorCodes::InvalidInput)]
+    pub reward_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        seeds=[b"rewards", campaign.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut, token::mint = reward_mint, token::authority = funder)]
+    pub funder_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// holds the rewards of campaigns paying in lamports, replaces the reward token accounts
+    #[account(mut, seeds=[b"sol_rewards", campaign.key().as_ref()], bump)]
+    pub sol_reward_vault: Option<SystemAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,5 +1,15 @@
 pub mod create_campaign;
 pub mod close_campaign;
+pub mod slash_stake;
+pub mod restore_stake;
+pub mod finalize_slash;
+pub mod withdraw_revenue;
+pub mod fund_campaign;
 
 pub use create_campaign::*;
-pub use close_campaign::*;
\ No newline at end of file
+pub use close_campaign::*;
+pub use slash_stake::*;
+pub use restore_stake::*;
+pub use finalize_slash::*;
+pub use withdraw_revenue::*;
+pub use fund_campaign::*;
\ No newline at end of file
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{errors::ErrorCodes, execute_token_transfer, state::{CampaignPlayer, StakeSlash}, Campaign, House, ACCOUNT_VERSION};
+
+pub fn restore_stake<'info>(ctx: Context<'_, '_, '_, 'info, RestoreStake<'info>>) -> Result<()> {
+    let now_ts = Clock::get()?.unix_timestamp;
+    let amount = ctx.accounts.stake_slash.amount;
+    require!(now_ts <= ctx.accounts.stake_slash.dispute_ends_at, ErrorCodes::SlashDisputeClosed);
+
+    let campaign = &mut ctx.accounts.campaign;
+    campaign.pending_slashes = campaign.pending_slashes.saturating_sub(amount);
+
+    match (ctx.accounts.campaign_player.stake_info.as_mut(), &ctx.accounts.game_deposit_vault, &ctx.accounts.game_deposit_mint, &ctx.accounts.player_token_account, &ctx.accounts.token_program) {
+        (Some(stake_info), None, None, None, None) => {
+            campaign.add_stake(stake_info, amount, now_ts)?;
+        },
+        // the stake was already claimed, the slashed part goes straight back to the player
+        (None, Some(game_deposit_vault), Some(game_deposit_mint), Some(player_token_account), Some(token_program)) => {
+            let campaign_key = campaign.key();
+            execute_token_transfer(
+                amount,
+                game_deposit_mint.decimals,
+                game_deposit_vault.to_account_info(),
+                game_deposit_mint.to_account_info(),
+                player_token_account.to_account_info(),
+                ctx.accounts.campaign_auth.to_account_info(),
+                token_program.to_account_info(),
+                Some(&[&[campaign_key.as_ref(), &[campaign.auth_bump]]]),
+                ctx.remaining_accounts)?;
+        },
+        (_, _, _, _, _) => return err!(ErrorCodes::InvalidInput),
+    }
+    msg!("slashed stake restored: {}", amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RestoreStake<'info> {
+    #[account(has_one=house_admin, constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub house: Box<Account<'info, House>>,
+    #[account(mut, has_one=house, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+    #[account(mut, has_one=campaign, constraint = campaign_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign_player: Box<Account<'info, CampaignPlayer>>,
+    #[account(mut, close=authority, has_one=campaign, has_one=campaign_player, has_one=authority,
+        seeds=[b"slash", campaign_player.key().as_ref()], bump)]
+    pub stake_slash: Box<Account<'info, StakeSlash>>,
+    /// CHECK: receives the slash rent, checked against stake_slash
+    #[account(mut)]
+    pub authority: AccountInfo<'info>,
+    pub house_admin: Signer<'info>,
+    /// CHECK: the campaign auth PDA
+    #[account(seeds=[campaign.key().as_ref()], bump)]
+    pub campaign_auth: AccountInfo<'info>,
+
+    #[account(mut, token::mint=game_deposit_mint, seeds=[b"player_deposit", campaign.key().as_ref()], bump)]
+    pub game_deposit_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(constraint = campaign.token_config.is_some_and(|c| c.spending_mint == game_deposit_mint.key()) @ ErrorCodes::InvalidInput)]
+    pub game_deposit_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        mut,
+        token::mint = game_deposit_mint,
+        token::authority = campaign_player.player_identity.pubkey,
+    )]
+    pub player_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
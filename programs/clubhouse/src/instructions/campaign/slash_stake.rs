@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::{errors::ErrorCodes, state::{CampaignPlayer, StakeSlash}, Campaign, House, PenaltyDestination, ACCOUNT_VERSION};
+
+pub fn slash_stake(ctx: Context<SlashStake>, amount: u64, reason_code: u16, destination: PenaltyDestination) -> Result<()> {
+    let token_config = ctx.accounts.campaign.token_config.ok_or(ErrorCodes::SlashingDisabled)?;
+    let dispute_seconds = token_config.slash_dispute_seconds.ok_or(ErrorCodes::SlashingDisabled)?;
+    let authority = ctx.accounts.authority.key();
+    require!(
+        authority == ctx.accounts.house.house_admin || ctx.accounts.campaign.is_oracle(&authority),
+        ErrorCodes::NotClubAdmin
+    );
+    require!(destination != PenaltyDestination::Creator, ErrorCodes::InvalidInput);
+    // the slashed tokens are paid out as rewards, so the reward vault must hold the staked mint
+    if destination == PenaltyDestination::RewardPool {
+        require!(ctx.accounts.campaign.reward_mint == token_config.spending_mint, ErrorCodes::InvalidInput);
+    }
+    require!(amount > 0, ErrorCodes::InvalidInput);
+
+    let now_ts = Clock::get()?.unix_timestamp;
+    let campaign = &mut ctx.accounts.campaign;
+    let stake_info = ctx.accounts.campaign_player.stake_info.as_mut().ok_or(ErrorCodes::NoStake)?;
+    require!(amount <= stake_info.amount, ErrorCodes::AmountTooHigh);
+
+    // the tokens stay in the deposit vault but no longer count as the player's stake
+    campaign.remove_stake(stake_info, amount, now_ts)?;
+    campaign.pending_slashes = campaign.pending_slashes.checked_add(amount).ok_or(ErrorCodes::AmountTooHigh)?;
+
+    ctx.accounts.stake_slash.set_inner(StakeSlash {
+        campaign: campaign.key(),
+        campaign_player: ctx.accounts.campaign_player.key(),
+        authority,
+        amount,
+        reason_code,
+        destination,
+        dispute_ends_at: now_ts.checked_add(dispute_seconds).ok_or(ErrorCodes::InvalidInput)?,
+    });
+    msg!("stake slashed: {}, reason: {}", amount, reason_code);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SlashStake<'info> {
+    #[account(constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub house: Box<Account<'info, House>>,
+    #[account(mut, has_one=house, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+    #[account(mut, has_one=campaign, constraint = campaign_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign_player: Box<Account<'info, CampaignPlayer>>,
+    #[account(init, payer=authority, space=8+StakeSlash::INIT_SPACE,
+        seeds=[b"slash", campaign_player.key().as_ref()], bump)]
+    pub stake_slash: Box<Account<'info, StakeSlash>>,
+    /// the house admin or a campaign oracle
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
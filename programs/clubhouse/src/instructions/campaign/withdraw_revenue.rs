@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface}};
+
+use crate::{errors::ErrorCodes, execute_token_transfer, Campaign, ACCOUNT_VERSION};
+
+pub fn withdraw_revenue<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawRevenue<'info>>) -> Result<()> {
+    let amount = ctx.accounts.campaign.creator_revenue;
+    require!(amount > 0, ErrorCodes::InsufficientFunds);
+
+    let campaign_key = ctx.accounts.campaign.key();
+    execute_token_transfer(
+        amount,
+        ctx.accounts.game_deposit_mint.decimals,
+        ctx.accounts.game_deposit_vault.to_account_info(),
+        ctx.accounts.game_deposit_mint.to_account_info(),
+        ctx.accounts.revenue_withdrawal_account.to_account_info(),
+        ctx.accounts.campaign_auth.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        Some(&[&[campaign_key.as_ref(), &[ctx.accounts.campaign.auth_bump]]]),
+        ctx.remaining_accounts)?;
+    ctx.accounts.campaign.creator_revenue = 0;
+    msg!("revenue withdrawn: {}", amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRevenue<'info> {
+    #[account(mut, has_one=creator, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+    /// CHECK: the campaign auth PDA
+    #[account(seeds=[campaign.key().as_ref()], bump)]
+    pub campaign_auth: AccountInfo<'info>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(mut, token::mint=game_deposit_mint, seeds=[b"player_deposit", campaign.key().as_ref()], bump)]
+    pub game_deposit_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(constraint = campaign.token_config.is_some_and(|c| c.spending_mint == game_deposit_mint.key()) @ ErrorCodes::InvalidInput)]
+    pub game_deposit_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(init_if_needed,
+        payer=creator,
+        associated_token::authority = creator,
+        associated_token::mint = game_deposit_mint,
+        associated_token::token_program = token_program
+    )]
+    pub revenue_withdrawal_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
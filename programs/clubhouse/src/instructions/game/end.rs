@@ -1,16 +1,28 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token::AssociatedToken, metadata::MetadataAccount, token::{Mint, Token, TokenAccount}};
-use crate::{execute_lamport_transfer, execute_token_transfer, instructions::execute_token_burn, metadata_is_collection, state::{SimplifiedAssetV1, UpdateAuthority}, IdentityType, PlayerIdentity};
+use anchor_spl::{associated_token::AssociatedToken, metadata::{Metadata, MetadataAccount}, token_interface::{Mint, TokenAccount, TokenInterface}};
+use crate::{execute_core_remove_freeze, execute_core_thaw, execute_lamport_transfer, execute_nft_revoke, execute_nft_unlock, instructions::NftLockAccounts, is_programmable, MPL_CORE_ID, execute_token_transfer, get_amount_after_transfer_fee, instructions::execute_token_burn, metadata_is_collection, state::load_core_asset, verify_group_member, CompressedNftProof, IdentityType, PlayerIdentity};
 
-use crate::{errors::ErrorCodes, Campaign, CampaignPlayer, House};
+use crate::{errors::ErrorCodes, Campaign, CampaignPlayer, House, ACCOUNT_VERSION};
 
-pub fn end_game(ctx: Context<EndGame>, amount_won: u64) -> Result<()> {
+pub fn end_game<'info>(ctx: Context<'_, '_, '_, 'info, EndGame<'info>>, amount_won: u64, compressed_nft: Option<CompressedNftProof>) -> Result<()> {
+    ctx.accounts.validate_metadata_nft()?;
     ctx.accounts.validate_core_nft()?;
+    ctx.accounts.validate_member_nft()?;
+    ctx.accounts.validate_compressed_nft(&compressed_nft, ctx.remaining_accounts)?;
+    if ctx.accounts.campaign.nft_config.is_some_and(|c| c.lock_player_nft) {
+        ctx.accounts.unlock_player_nft()?;
+    }
+    // proof nodes come first, any transfer hook accounts follow them
+    let proof_len = compressed_nft.as_ref().map_or(0, |proof| proof.proof_len as usize);
+    let transfer_accounts = ctx.remaining_accounts.get(proof_len..).unwrap_or(&[]);
     let campaign_player = &mut ctx.accounts.campaign_player;
     match (ctx.accounts.campaign.nft_config, &ctx.accounts.player_nft_metadata, &ctx.accounts.player_nft_token_account, &ctx.accounts.player_core_nft) {
         (None, None, None, None) => {},
         (Some(_), Some(_), Some(_), None) => {},
         (Some(_) , None, None, Some(_)) => {},
+        (Some(_), None, Some(_), None) if ctx.accounts.player_member_mint.is_some() => {},
+        (Some(_), None, None, None) if compressed_nft.is_some() => {},
+        (Some(nft_config), None, None, None) if nft_config.tokenized_energy => {},
         (None, _, _, _) => return err!(ErrorCodes::InvalidInput),
         (Some(_), _, _, _) => return err!(ErrorCodes::UnexpectedMetadata),
         
@@ -21,28 +33,45 @@ pub fn end_game(ctx: Context<EndGame>, amount_won: u64) -> Result<()> {
     }
 
     require!(campaign_player.in_game, ErrorCodes::RewardsUnavailable);
+    let mode = ctx.accounts.campaign.get_game_mode(campaign_player.game_mode)?;
+    let oracle_key = ctx.accounts.campaign.get_oracle(&mode);
    match &ctx.accounts.oracle {
         Some(oracle) => {
-            if ctx.accounts.campaign.house_config_snapshot.oracle_key != oracle.key() {
+            if oracle_key != oracle.key() {
                 return err!(ErrorCodes::OracleMismatch)
             }
         }
         None => {
-            if ctx.accounts.campaign.house_config_snapshot.oracle_key != System::id() {
+            if oracle_key != System::id() {
                 return err!(ErrorCodes::OracleMismatch)
             }
         }
    } 
    
    if amount_won > 0 {
-        if amount_won > ctx.accounts.campaign.max_rewards_per_game {return err!(ErrorCodes::AmountTooHigh)}
+        if amount_won > campaign_player.game_max_rewards {return err!(ErrorCodes::AmountTooHigh)}
    
-        execute_token_transfer(amount_won,
-            ctx.accounts.reward_vault.to_account_info(), 
-            ctx.accounts.player_reward_token_account.to_account_info(),
-            ctx.accounts.campaign_auth.to_account_info(),
-            ctx.accounts.token_program.to_account_info(), 
-            Some(&[&[ctx.accounts.campaign.key().as_ref(),&[ctx.accounts.campaign.auth_bump]]]))?;
+        match (&ctx.accounts.reward_mint, &ctx.accounts.reward_vault, &ctx.accounts.player_reward_token_account, &ctx.accounts.sol_reward_vault) {
+            (Some(reward_mint), Some(reward_vault), Some(player_reward_token_account), None) => {
+                execute_token_transfer(amount_won,
+                    ctx.accounts.campaign.reward_mint_decimals,
+                    reward_vault.to_account_info(), 
+                    reward_mint.to_account_info(),
+                    player_reward_token_account.to_account_info(),
+                    ctx.accounts.campaign_auth.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(), 
+                    Some(&[&[ctx.accounts.campaign.key().as_ref(),&[ctx.accounts.campaign.auth_bump]]]),
+                    transfer_accounts)?;
+            },
+            (None, None, None, Some(sol_reward_vault)) => {
+                execute_lamport_transfer(amount_won,
+                    sol_reward_vault.to_account_info(),
+                    ctx.accounts.user.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    Some(&[&[b"sol_rewards", ctx.accounts.campaign.key().as_ref(), &[ctx.accounts.campaign.sol_rewards_bump]]]))?;
+            },
+            (_, _, _, _) => return err!(ErrorCodes::InvalidInput),
+        }
             
         if ctx.accounts.campaign.house_config_snapshot.claim_fee > 0 {
             execute_lamport_transfer(
@@ -63,11 +92,12 @@ pub fn end_game(ctx: Context<EndGame>, amount_won: u64) -> Result<()> {
         }
     }
     if ctx.accounts.campaign.burn_remainder {
-        let remainder = ctx.accounts.campaign.max_rewards_per_game.saturating_sub(amount_won);
+        let remainder = campaign_player.game_max_rewards.saturating_sub(amount_won);
         if remainder > 0 {
+            let (reward_mint, reward_vault) = ctx.accounts.reward_mint.as_ref().zip(ctx.accounts.reward_vault.as_ref()).ok_or(ErrorCodes::InvalidInput)?;
             execute_token_burn(remainder,
-                ctx.accounts.reward_mint.to_account_info(),
-                ctx.accounts.reward_vault.to_account_info(),
+                reward_mint.to_account_info(),
+                reward_vault.to_account_info(),
                 ctx.accounts.campaign_auth.to_account_info(),
                 ctx.accounts.token_program.to_account_info(), 
                 Some(&[&[ctx.accounts.campaign.key().as_ref(),&[ctx.accounts.campaign.auth_bump]]]))?;
@@ -77,23 +107,28 @@ pub fn end_game(ctx: Context<EndGame>, amount_won: u64) -> Result<()> {
     }
     campaign_player.in_game = false;
     campaign_player.games_played += 1;
-    campaign_player.rewards_claimed += amount_won;
+    // the transfer fee of the reward mint is withheld from the player's side
+    let amount_received = match &ctx.accounts.reward_mint {
+        Some(reward_mint) => get_amount_after_transfer_fee(&reward_mint.to_account_info(), amount_won)?,
+        None => amount_won,
+    };
+    campaign_player.rewards_claimed += amount_received;
 
     ctx.accounts.campaign.active_games = ctx.accounts.campaign.active_games.saturating_sub(1);
     ctx.accounts.campaign.total_games +=1;
     ctx.accounts.campaign.rewards_available = ctx.accounts.campaign.rewards_available - amount_won;
 
-    //TODO: fix 
-    ctx.accounts.campaign.reserved_rewards = ctx.accounts.campaign.reserved_rewards.saturating_sub(ctx.accounts.campaign.max_rewards_per_game);
+    ctx.accounts.campaign.reserved_rewards = ctx.accounts.campaign.reserved_rewards.saturating_sub(campaign_player.game_max_rewards);
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(amount_won: u64, compressed_nft: Option<CompressedNftProof>)]
 pub struct EndGame<'info> {
-    #[account(mut)]
+    #[account(mut, constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub house: Box<Account<'info, House>>,
 
-    #[account(mut, has_one=house, has_one=reward_mint)]
+    #[account(mut, has_one=house, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub campaign: Box<Account<'info, Campaign>>,
     
     /// CHECK: campaign proxy signer
@@ -104,76 +139,201 @@ pub struct EndGame<'info> {
         seeds = [
             b"player", 
             campaign.key().as_ref(), 
-            &match (&player_nft_metadata, &player_core_nft) {
-                (Some(metadata), _) => metadata.mint.to_bytes(),
-                (None, Some(core_nft)) => core_nft.key().to_bytes(),
-                (None, None) => user.key().to_bytes(),
+            &match (&player_nft_metadata, &player_core_nft, &player_member_mint, &compressed_nft, &merkle_tree) {
+                (Some(metadata), _, _, _, _) => metadata.mint.to_bytes(),
+                (None, Some(core_nft), _, _, _) => core_nft.key().to_bytes(),
+                (None, None, Some(member_mint), _, _) => member_mint.key().to_bytes(),
+                (None, None, None, Some(cnft), Some(tree)) => cnft.asset_id(&tree.key()).to_bytes(),
+                (None, None, None, _, _) => user.key().to_bytes(),
             }[..]
         ],
-        bump)]
+        bump,
+        constraint = campaign_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub campaign_player: Box<Account<'info, CampaignPlayer>>,
 
-    #[account(
-        constraint = player_nft_metadata.as_ref().is_some_and(|m| m.mint == player_nft_token_account.mint ), 
+    #[account(mut,
+        constraint = player_nft_metadata.as_ref().map(|m| m.mint).or(player_member_mint.as_ref().map(|m| m.key())) == Some(player_nft_token_account.mint), 
         constraint = player_nft_token_account.owner == user.key() @ ErrorCodes::TokenOwnerMismatch, 
         constraint = player_nft_token_account.amount == 1 @ ErrorCodes::OwnerBalanceMismatch,
     )]
-    pub player_nft_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    pub player_nft_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
 
-    #[account(constraint = metadata_is_collection(&player_nft_metadata,&campaign.nft_config.unwrap().collection).is_ok())]
+    /// validated against the player's campaign collection in the handler
+    #[account(mut)]
     pub player_nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
 
-    /// CHECK: Custom validation for mpl-core asset
-    #[account()]
+    /// CHECK: Custom validation for mpl-core asset, written by mpl-core when the campaign locks it
+    #[account(mut)]
     pub player_core_nft: Option<AccountInfo<'info>>,
 
+    /// CHECK: Custom validation for mpl-core collection, needed when the campaign collection is its update authority or delegate
     #[account(mut)]
-    pub reward_mint: Box<Account<'info, Mint>>,
+    pub player_core_collection: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token-2022 NFT mint, validated through its group member extension
+    #[account()]
+    pub player_member_mint: Option<AccountInfo<'info>>,
+
+    /// CHECK: Bubblegum tree of a compressed NFT identity, validated against the proof
+    #[account()]
+    pub merkle_tree: Option<AccountInfo<'info>>,
+
+    /// CHECK: token record of a programmable NFT, required to unlock it
+    #[account(mut)]
+    pub player_token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: mint of the player NFT, required to lock it
+    #[account(constraint = player_nft_metadata.as_ref().is_some_and(|m| m.mint == player_nft_mint.key()) @ ErrorCodes::InvalidInput)]
+    pub player_nft_mint: Option<AccountInfo<'info>>,
+
+    /// CHECK: master edition of the player NFT, validated by token metadata
+    #[account()]
+    pub player_nft_edition: Option<AccountInfo<'info>>,
+
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    /// CHECK: instructions sysvar, required to lock token metadata NFTs
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<AccountInfo<'info>>,
+
+    /// CHECK: rule set program of a pNFT, validated by token metadata
+    #[account()]
+    pub authorization_rules_program: Option<AccountInfo<'info>>,
+
+    /// CHECK: rule set of a pNFT, validated by token metadata
+    #[account()]
+    pub authorization_rules: Option<AccountInfo<'info>>,
+
+    /// CHECK: required to lock mpl-core assets
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: Option<AccountInfo<'info>>,
+
+    /// omitted with the reward token accounts when the campaign pays rewards in lamports
+    #[account(mut, address = campaign.reward_mint @ ErrorCodes::InvalidInput, mint::token_program = token_program)]
+    pub reward_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
 
     #[account(
         mut,
         seeds=[b"rewards", campaign.key().as_ref()], 
         bump, 
         token::mint = reward_mint, 
-        token::authority = campaign_auth
+        token::authority = campaign_auth,
+        token::token_program = token_program,
     )]
-    pub reward_vault: Box<Account<'info, TokenAccount>>,
+    pub reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     #[account(init_if_needed, payer=user,
         associated_token::mint = reward_mint,
         associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
-    pub player_reward_token_account : Box<Account<'info, TokenAccount>>,
+    pub player_reward_token_account : Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// pays the rewards of campaigns funded in lamports
+    #[account(mut, seeds=[b"sol_rewards", campaign.key().as_ref()], bump)]
+    pub sol_reward_vault: Option<SystemAccount<'info>>,
 
     #[account(mut)]
     pub user: Signer<'info>,
     pub oracle: Option<Signer<'info>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>
 }
 
-impl EndGame<'_> {
+impl<'info> EndGame<'info> {
+    /// Releases the freeze taken by start_game
+    pub fn unlock_player_nft(&self) -> Result<()> {
+        let campaign_key = self.campaign.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[campaign_key.as_ref(), &[self.campaign.auth_bump]]];
+        let campaign_auth = &self.campaign_auth;
+        match (&self.player_nft_metadata, &self.player_nft_token_account, &self.player_core_nft, &self.merkle_tree) {
+            (Some(metadata), Some(token_account), None, None) => {
+                let metadata_info = metadata.to_account_info();
+                let token_info = token_account.to_account_info();
+                let system_program_info = self.system_program.to_account_info();
+                let token_program_info = self.token_program.to_account_info();
+                let token_metadata_program = self.token_metadata_program.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?.to_account_info();
+                let accounts = NftLockAccounts {
+                    token_metadata_program: &token_metadata_program,
+                    delegate: campaign_auth,
+                    owner: self.user.as_ref(),
+                    payer: self.user.as_ref(),
+                    metadata: &metadata_info,
+                    edition: self.player_nft_edition.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?,
+                    token_record: self.player_token_record.as_ref(),
+                    mint: self.player_nft_mint.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?,
+                    token: &token_info,
+                    system_program: &system_program_info,
+                    sysvar_instructions: self.sysvar_instructions.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?,
+                    token_program: &token_program_info,
+                    authorization_rules_program: self.authorization_rules_program.as_ref(),
+                    authorization_rules: self.authorization_rules.as_ref(),
+                    programmable: is_programmable(metadata),
+                    delegated: true,
+                };
+                execute_nft_unlock(&accounts, signer_seeds)?;
+                execute_nft_revoke(&accounts)
+            },
+            (None, None, Some(core_nft), None) => {
+                let mpl_core_program = self.mpl_core_program.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?;
+                execute_core_thaw(
+                    core_nft.to_account_info(),
+                    self.player_core_collection.clone(),
+                    self.user.to_account_info(),
+                    campaign_auth.to_account_info(),
+                    self.system_program.to_account_info(),
+                    mpl_core_program.to_account_info(),
+                    signer_seeds)?;
+                execute_core_remove_freeze(
+                    core_nft.to_account_info(),
+                    self.player_core_collection.clone(),
+                    self.user.to_account_info(),
+                    self.system_program.to_account_info(),
+                    mpl_core_program.to_account_info())
+            },
+            (None, None, None, None) => Ok(()), // tokenized energy holder, nothing to release
+            (_, _, _, _) => err!(ErrorCodes::NftLockUnsupported),
+        }
+    }
+    /// The campaign collection the player joined with
+    pub fn player_collection(&self) -> Result<Pubkey> {
+        Ok(self.campaign.get_collection(self.campaign_player.collection_index)?.collection)
+    }
+    pub fn validate_metadata_nft(&self) -> Result<()> {
+        if let Some(metadata) = &self.player_nft_metadata {
+            let nft_config = self.campaign.nft_config.ok_or(ErrorCodes::UnexpectedMetadata)?;
+            metadata_is_collection(metadata, &self.player_collection()?, nft_config.collection_verification)?;
+        }
+        Ok(())
+    }
     pub fn validate_core_nft(&self) -> Result<()> {
         if let Some(core_nft_info) = &self.player_core_nft {
-            let nft = SimplifiedAssetV1::from_account_info(core_nft_info)?;
-            
-            if nft.owner != self.user.key() {
-                return err!(ErrorCodes::TokenOwnerMismatch);
-            }
-            
-            if let Some(nft_config) = self.campaign.nft_config {
-                if nft.update_authority != UpdateAuthority::Collection(nft_config.collection) {
-                    return err!(ErrorCodes::OwnerBalanceMismatch);
-                }
-            } else {
-                return err!(ErrorCodes::InvalidInput);
-            }
+            load_core_asset(core_nft_info, self.player_core_collection.as_ref(), &self.user.key(), &self.player_collection()?)?;
         }
         
         Ok(())
     }
+    pub fn validate_member_nft(&self) -> Result<()> {
+        if let Some(member_mint) = &self.player_member_mint {
+            require!(self.player_nft_token_account.is_some(), ErrorCodes::InvalidInput);
+            verify_group_member(member_mint, &self.player_collection()?)?;
+        }
+        Ok(())
+    }
+    pub fn validate_compressed_nft(&self, compressed_nft: &Option<CompressedNftProof>, remaining_accounts: &[AccountInfo]) -> Result<()> {
+        match (compressed_nft, &self.merkle_tree) {
+            (Some(proof), Some(merkle_tree)) => {
+                let nft_config = self.campaign.nft_config.ok_or(ErrorCodes::InvalidInput)?;
+                proof.verify(merkle_tree, &self.user.key(), &self.player_collection()?, nft_config.collection_verification, remaining_accounts)?;
+                Ok(())
+            },
+            (None, None) => Ok(()),
+            (_, _) => err!(ErrorCodes::InvalidInput),
+        }
+    }
+
     pub fn get_player_identity(&self) -> Result<PlayerIdentity> {
         match (self.campaign.nft_config, &self.player_nft_metadata, &self.player_core_nft) {
             (None, None, None) => Ok(PlayerIdentity{
@@ -181,6 +341,10 @@ impl EndGame<'_> {
                     pubkey: self.user.key(),
                 }),
             (None, Some(_), None) => err!(ErrorCodes::UnexpectedMetadata),
+            (Some(_), None, None) if self.player_member_mint.is_some() => Ok(PlayerIdentity{
+                    identity_type: IdentityType::Token2022Nft,
+                    pubkey: self.player_member_mint.as_ref().unwrap().key(),
+                }),
             (Some(_), None, None) => err!(ErrorCodes::MissingMetadata),
             (Some(_), Some(metadata), None) => Ok(PlayerIdentity{
              identity_type: IdentityType::Nft,
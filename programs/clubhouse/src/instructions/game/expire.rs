@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{metadata::{Metadata, MetadataAccount}, token_interface::{TokenAccount, TokenInterface}};
+use crate::{execute_core_thaw, execute_nft_unlock, instructions::NftLockAccounts, is_programmable, MPL_CORE_ID};
+
+use crate::{errors::ErrorCodes, Campaign, CampaignPlayer, IdentityType, ACCOUNT_VERSION};
+
+/// Closes a game the oracle never ended once the campaign max_game_seconds have passed.
+/// Anyone may call it, a locked player NFT is unlocked and the reserved rewards are released without payout
+pub fn expire_game(ctx: Context<ExpireGame>) -> Result<()> {
+    let nft_config = ctx.accounts.campaign.nft_config.ok_or(ErrorCodes::InvalidInput)?;
+    let max_game_seconds = nft_config.max_game_seconds.ok_or(ErrorCodes::InvalidInput)?;
+    require!(ctx.accounts.campaign_player.in_game, ErrorCodes::InvalidInput);
+    let now_ts = Clock::get()?.unix_timestamp;
+    require!(now_ts >= ctx.accounts.campaign_player.game_start_time.saturating_add(max_game_seconds), ErrorCodes::GameNotExpired);
+
+    if nft_config.lock_player_nft {
+        ctx.accounts.unlock_player_nft()?;
+    }
+
+    let campaign_player = &mut ctx.accounts.campaign_player;
+    campaign_player.in_game = false;
+    let campaign = &mut ctx.accounts.campaign;
+    campaign.active_games = campaign.active_games.saturating_sub(1);
+    campaign.reserved_rewards = campaign.reserved_rewards.saturating_sub(campaign_player.game_max_rewards);
+    msg!("game expired: {}", campaign_player.key());
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExpireGame<'info> {
+    #[account(mut, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+
+    /// CHECK: campaign proxy signer, the lock delegate
+    #[account(seeds=[campaign.key().as_ref()], bump)]
+    pub campaign_auth: AccountInfo<'info>,
+
+    #[account(mut, has_one=campaign, constraint = campaign_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign_player: Box<Account<'info, CampaignPlayer>>,
+
+    #[account(mut,
+        constraint = player_nft_token_account.mint == campaign_player.player_identity.pubkey @ ErrorCodes::PlayerIdentityMismatch,
+        constraint = player_nft_token_account.amount == 1 @ ErrorCodes::OwnerBalanceMismatch,
+    )]
+    pub player_nft_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: holder of the player NFT, the owner of its token account
+    #[account(constraint = player_nft_token_account.as_ref().is_some_and(|t| t.owner == player_nft_owner.key()) @ ErrorCodes::TokenOwnerMismatch)]
+    pub player_nft_owner: Option<AccountInfo<'info>>,
+
+    #[account(mut, constraint = player_nft_metadata.mint == campaign_player.player_identity.pubkey @ ErrorCodes::PlayerIdentityMismatch)]
+    pub player_nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    /// CHECK: token record of a programmable NFT, required to unlock it
+    #[account(mut)]
+    pub player_token_record: Option<AccountInfo<'info>>,
+
+    /// CHECK: mint of the player NFT, required to unlock it
+    #[account(constraint = player_nft_mint.key() == campaign_player.player_identity.pubkey @ ErrorCodes::PlayerIdentityMismatch)]
+    pub player_nft_mint: Option<AccountInfo<'info>>,
+
+    /// CHECK: master edition of the player NFT, validated by token metadata
+    #[account()]
+    pub player_nft_edition: Option<AccountInfo<'info>>,
+
+    /// CHECK: the locked mpl-core asset, thawed by campaign_auth
+    #[account(mut, constraint = player_core_nft.key() == campaign_player.player_identity.pubkey @ ErrorCodes::PlayerIdentityMismatch)]
+    pub player_core_nft: Option<AccountInfo<'info>>,
+
+    /// CHECK: collection of the mpl-core asset, validated by mpl-core
+    #[account(mut)]
+    pub player_core_collection: Option<AccountInfo<'info>>,
+
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    /// CHECK: instructions sysvar, required to unlock token metadata NFTs
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<AccountInfo<'info>>,
+
+    /// CHECK: rule set program of a pNFT, validated by token metadata
+    #[account()]
+    pub authorization_rules_program: Option<AccountInfo<'info>>,
+
+    /// CHECK: rule set of a pNFT, validated by token metadata
+    #[account()]
+    pub authorization_rules: Option<AccountInfo<'info>>,
+
+    /// CHECK: required to unlock mpl-core assets
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: Option<AccountInfo<'info>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExpireGame<'info> {
+    /// Releases the freeze taken by start_game, the delegate approval stays until the next end_game
+    pub fn unlock_player_nft(&self) -> Result<()> {
+        let campaign_key = self.campaign.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[campaign_key.as_ref(), &[self.campaign.auth_bump]]];
+        match (self.campaign_player.player_identity.identity_type, &self.player_nft_metadata, &self.player_nft_token_account, &self.player_core_nft) {
+            (IdentityType::Nft, Some(metadata), Some(token_account), None) => {
+                let metadata_info = metadata.to_account_info();
+                let token_info = token_account.to_account_info();
+                let system_program_info = self.system_program.to_account_info();
+                let payer_info = self.payer.to_account_info();
+                let token_program_info = self.token_program.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?.to_account_info();
+                let token_metadata_program = self.token_metadata_program.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?.to_account_info();
+                let accounts = NftLockAccounts {
+                    token_metadata_program: &token_metadata_program,
+                    delegate: &self.campaign_auth,
+                    owner: self.player_nft_owner.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?,
+                    payer: &payer_info,
+                    metadata: &metadata_info,
+                    edition: self.player_nft_edition.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?,
+                    token_record: self.player_token_record.as_ref(),
+                    mint: self.player_nft_mint.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?,
+                    token: &token_info,
+                    system_program: &system_program_info,
+                    sysvar_instructions: self.sysvar_instructions.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?,
+                    token_program: &token_program_info,
+                    authorization_rules_program: self.authorization_rules_program.as_ref(),
+                    authorization_rules: self.authorization_rules.as_ref(),
+                    programmable: is_programmable(metadata),
+                    delegated: true,
+                };
+                execute_nft_unlock(&accounts, signer_seeds)
+            },
+            (IdentityType::MplCore, None, None, Some(core_nft)) => {
+                let mpl_core_program = self.mpl_core_program.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?;
+                execute_core_thaw(
+                    core_nft.to_account_info(),
+                    self.player_core_collection.clone(),
+                    self.payer.to_account_info(),
+                    self.campaign_auth.to_account_info(),
+                    self.system_program.to_account_info(),
+                    mpl_core_program.to_account_info(),
+                    signer_seeds)
+            },
+            // tokenized energy holder, nothing was locked
+            (IdentityType::User, None, None, None) => Ok(()),
+            (_, _, _, _) => err!(ErrorCodes::NftLockAccountsRequired),
+        }
+    }
+}
@@ -1,6 +1,9 @@
 mod start;
 mod end;
+mod expire;
 
 pub use start::*;
 
-pub use end::*;
\ No newline at end of file
+pub use end::*;
+
+pub use expire::*;
\ No newline at end of file
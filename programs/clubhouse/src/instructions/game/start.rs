@@ -1,14 +1,30 @@
 use std::ops::AddAssign;
 
 use anchor_lang::prelude::*;
-use anchor_spl::{metadata::MetadataAccount, token_interface::{Mint, TokenAccount, TokenInterface}};
-use crate::{errors, execute_token_burn, execute_token_transfer, metadata_is_collection, state::{SimplifiedAssetV1, UpdateAuthority}, IdentityType, PlayerIdentity, StakeInfo, TokenUse};
+use anchor_spl::{metadata::{Metadata, MetadataAccount}, token_interface::{Mint, TokenAccount, TokenInterface}};
+use crate::{errors, execute_core_freeze, execute_lamport_transfer, execute_nft_lock, execute_token_burn, instructions::NftLockAccounts, is_programmable, MPL_CORE_ID, execute_token_transfer, get_amount_after_transfer_fee, metadata_is_collection, state::load_owned_core_asset, verify_group_member, validate_token_record, CompressedNftProof, CoreAsset, HousePlayer, TraitBonus, TraitProof, IdentityType, PlayerIdentity, StakeInfo, TokenUse};
 
-use crate::{errors::ErrorCodes, Campaign, CampaignPlayer, House};
+use crate::{errors::ErrorCodes, Campaign, CampaignPlayer, House, ACCOUNT_VERSION};
 
-pub fn start_game(ctx: Context<StartGame>) -> Result<()> {
-    ctx.accounts.validate_core_nft()?;
-    let inferred_identity = ctx.accounts.get_player_identity()?;
+pub fn start_game<'info>(ctx: Context<'_, '_, '_, 'info, StartGame<'info>>, game_mode: u8, compressed_nft: Option<CompressedNftProof>, trait_proof: Option<TraitProof>, max_price: u64) -> Result<()> {
+    let collection_index = [
+        ctx.accounts.validate_metadata_nft()?,
+        ctx.accounts.validate_core_nft()?,
+        ctx.accounts.validate_member_nft()?,
+        ctx.accounts.validate_compressed_nft(&compressed_nft, ctx.remaining_accounts)?,
+    ].into_iter().flatten().next().unwrap_or(0);
+    ctx.accounts.validate_programmable_nft()?;
+    // proof nodes come first, any transfer hook accounts follow them
+    let proof_len = compressed_nft.as_ref().map_or(0, |proof| proof.proof_len as usize);
+    let transfer_accounts = ctx.remaining_accounts.get(proof_len..).unwrap_or(&[]);
+    let inferred_identity = ctx.accounts.get_player_identity(&compressed_nft)?;
+    if let Some(nft_config) = ctx.accounts.campaign.nft_config {
+        require!(nft_config.allows_identity(inferred_identity.identity_type), ErrorCodes::IdentityTypeNotAllowed);
+    }
+    let trait_bonus = ctx.accounts.get_trait_bonus(&inferred_identity, &trait_proof)?;
+    if ctx.accounts.campaign.nft_config.is_some_and(|c| c.lock_player_nft) {
+        ctx.accounts.lock_player_nft()?;
+    }
     let campaign = &mut ctx.accounts.campaign;
     let campaign_player = &mut ctx.accounts.campaign_player;
 
@@ -17,35 +33,105 @@ pub fn start_game(ctx: Context<StartGame>) -> Result<()> {
 
     if campaign_player.player_identity.identity_type == IdentityType::None {
         //new or reinitialized campaign player
-        campaign_player.set_inner(CampaignPlayer::new(inferred_identity, &campaign)?);
+        campaign_player.set_inner(CampaignPlayer::new(inferred_identity, &campaign, collection_index)?);
         ctx.accounts.house.unique_players.add_assign(1);
         campaign.player_count.add_assign(1);
     }
 
     require!(campaign_player.player_identity == inferred_identity, ErrorCodes::PlayerIdentityMismatch);
+    campaign_player.trait_bonus = trait_bonus;
+
+    let mode = campaign.get_game_mode(game_mode)?;
+    let now_ts = Clock::get()?.unix_timestamp;
+    // the curve is evaluated before this game is counted
+    let payment_amount = campaign.game_price(&mode, campaign_player, now_ts);
 
     match (&campaign.nft_config, campaign.token_config, inferred_identity.identity_type, &ctx.accounts.player_nft_metadata) {
+        (None, Some(token_config), IdentityType::User, None) if token_config.token_use == TokenUse::StakeForEnergy => {
+            // paid with stake tier energy below, the stake is deposited through stake_tokens
+        },
+        (None, Some(token_config), IdentityType::User, None) if token_config.is_native() => {
+            let sol_deposit_vault = ctx.accounts.sol_deposit_vault.as_ref().ok_or(ErrorCodes::InvalidInput)?;
+            require!(payment_amount <= max_price, ErrorCodes::PriceAboveMax);
+            execute_lamport_transfer(
+                payment_amount,
+                ctx.accounts.user.to_account_info(),
+                sol_deposit_vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                None)?;
+            if token_config.token_use == TokenUse::Stake {
+                let stake_info = campaign_player.stake_info.as_mut().ok_or(ErrorCodes::NoStake)?;
+                campaign.add_stake(stake_info, payment_amount, now_ts)?;
+            }
+        },
         (None, Some(token_config), IdentityType::User, None) => {
-            let payment_amount = token_config.energy_price;
+            require!(payment_amount <= max_price, ErrorCodes::PriceAboveMax);
+            let game_deposit_mint = ctx.accounts.game_deposit_mint.as_ref().ok_or(ErrorCodes::InvalidInput)?;
+            require!(game_deposit_mint.key() == token_config.spending_mint, ErrorCodes::InvalidInput);
             match token_config.token_use {
+                crate::TokenUse::Pay if token_config.revenue_split.is_some() => {
+                    let shares = token_config.revenue_split.unwrap().shares(payment_amount, &campaign.house_config_snapshot);
+                    let players_deposit_account = ctx.accounts.players_deposit_account.as_ref().ok_or(ErrorCodes::InvalidInput)?;
+                    let pay_share = |amount: u64, destination: AccountInfo<'info>| -> Result<u64> {
+                        execute_token_transfer(
+                            amount,
+                            token_config.spending_mint_decimals,
+                            players_deposit_account.to_account_info(),
+                            game_deposit_mint.to_account_info(),
+                            destination,
+                            ctx.accounts.user.to_account_info(),
+                            ctx.accounts.token_program.to_account_info(),
+                            None,
+                            transfer_accounts)?;
+                        get_amount_after_transfer_fee(&game_deposit_mint.to_account_info(), amount)
+                    };
+                    if shares.creator > 0 {
+                        let game_deposit_vault = ctx.accounts.game_deposit_vault.as_ref().ok_or(ErrorCodes::InvalidInput)?;
+                        campaign.creator_revenue.add_assign(pay_share(shares.creator, game_deposit_vault.to_account_info())?);
+                    }
+                    if shares.house > 0 {
+                        let house_vault = ctx.accounts.house_vault.as_ref().ok_or(ErrorCodes::InvalidInput)?;
+                        ctx.accounts.house.unclaimed_house_fees.add_assign(pay_share(shares.house, house_vault.to_account_info())?);
+                    }
+                    if shares.reward_pool > 0 {
+                        let reward_vault = ctx.accounts.reward_vault.as_ref().ok_or(ErrorCodes::InvalidInput)?;
+                        campaign.rewards_available.add_assign(pay_share(shares.reward_pool, reward_vault.to_account_info())?);
+                    }
+                    if shares.burn > 0 {
+                        execute_token_burn(
+                            shares.burn,
+                            game_deposit_mint.to_account_info(),
+                            players_deposit_account.to_account_info(),
+                            ctx.accounts.user.to_account_info(),
+                            ctx.accounts.token_program.to_account_info(), None
+                        )?;
+                    }
+                },
                 crate::TokenUse::Stake | crate::TokenUse::Pay => 
                 {
                     execute_token_transfer(
                     payment_amount, 
+                    token_config.spending_mint_decimals,
                     ctx.accounts.players_deposit_account.as_ref().unwrap().to_account_info(),
+                    game_deposit_mint.to_account_info(),
                     ctx.accounts.game_deposit_vault.as_ref().unwrap().to_account_info(),
                      ctx.accounts.user.to_account_info(),
                       ctx.accounts.token_program.to_account_info(), 
-                      None)?;
+                      None,
+                      transfer_accounts)?;
                     
                     if token_config.token_use == crate::TokenUse::Stake {
-                        campaign_player.stake_info.as_mut().unwrap().amount.add_assign(payment_amount);
+                        // only what reached the vault can be returned
+                        let amount_received = get_amount_after_transfer_fee(&game_deposit_mint.to_account_info(), payment_amount)?;
+                        let stake_info = campaign_player.stake_info.as_mut().ok_or(ErrorCodes::NoStake)?;
+                        campaign.add_stake(stake_info, amount_received, now_ts)?;
                     }
                 }
+                crate::TokenUse::StakeForEnergy => return err!(ErrorCodes::InvalidInput),
                 crate::TokenUse::Burn => {
                     execute_token_burn(
                         payment_amount, 
-                        ctx.accounts.game_deposit_mint.as_ref().unwrap().to_account_info(), 
+                        game_deposit_mint.to_account_info(), 
                         ctx.accounts.players_deposit_account.as_ref().unwrap().to_account_info(), 
                         ctx.accounts.user.to_account_info(),
                         ctx.accounts.token_program.to_account_info(), None
@@ -58,6 +144,15 @@ pub fn start_game(ctx: Context<StartGame>) -> Result<()> {
         },
         (Some(_), None, IdentityType::MplCore, None) => {
             
+        },
+        (Some(_), None, IdentityType::CompressedNft, None) => {
+
+        },
+        (Some(_), None, IdentityType::Token2022Nft, None) => {
+
+        },
+        (Some(nft_config), None, IdentityType::User, None) if nft_config.tokenized_energy => {
+
         },
 
         (_, _, _ , _,) => return err!(ErrorCodes::InvalidInput),
@@ -71,21 +166,60 @@ pub fn start_game(ctx: Context<StartGame>) -> Result<()> {
         (None, None, Some(_), None, Some(_), None) => {}, // burn
         (None, None, Some(_), Some(_), Some(_), None) => {}, // pay
         (None, None, None, None, None, Some(_)) => {}, // core
+        (Some(_), None, None, None, None, None) if ctx.accounts.player_member_mint.is_some() => {}, // token-2022 nft
+        (None, None, None, None, None, None) => {}, // compressed nft or energy token holder
         (_, _, _, _, _, _) => return err!(ErrorCodes::InvalidInput),
     };
 
 
-    let max_rewards = campaign.max_rewards_per_game;
+    let max_rewards = match campaign.nft_config {
+        Some(_) => {
+            let collection_rewards = campaign.get_collection(campaign_player.collection_index)?.apply_multiplier(mode.max_rewards_per_game);
+            campaign_player.trait_bonus.apply_rewards(collection_rewards)
+        },
+        None => mode.max_rewards_per_game,
+    };
  
-    let now_ts = Clock::get()?.unix_timestamp;
     if campaign.time_span.is_expired(now_ts) {
         return err!(errors::ErrorCodes::CampaignExpired);
     }
-    let _ = campaign_player.recharge_energy(&campaign.nft_config, now_ts)?;
-    
-    campaign_player.spend_energy(1)?;
+    let tokenized_energy = campaign.nft_config.is_some_and(|c| c.tokenized_energy);
+    let house_energy = campaign.nft_config.is_some_and(|c| c.use_house_energy);
+    match (tokenized_energy, house_energy, ctx.accounts.house_player.as_mut(), &ctx.accounts.energy_mint, &ctx.accounts.player_energy_account) {
+        (true, false, None, Some(energy_mint), Some(player_energy_account)) => {
+            execute_token_burn(
+                mode.energy_cost.into(),
+                energy_mint.to_account_info(),
+                player_energy_account.to_account_info(),
+                ctx.accounts.user.to_account_info(),
+                ctx.accounts.token_program.to_account_info(), None
+            )?;
+        },
+        (false, true, Some(house_player), None, None) => {
+            let energy_config = ctx.accounts.house.player_energy.ok_or(ErrorCodes::InvalidEnergyConfig)?;
+            if house_player.player_identity.identity_type == IdentityType::None {
+                house_player.set_inner(HousePlayer::new(inferred_identity, ctx.accounts.house.key(), &energy_config)?);
+            }
+            require!(house_player.player_identity == inferred_identity, ErrorCodes::PlayerIdentityMismatch);
+            let _ = house_player.recharge_energy(&energy_config, now_ts);
+            house_player.spend_energy(mode.energy_cost)?;
+        },
+        (false, false, None, None, None) if campaign.token_config.is_some_and(|c| c.token_use == TokenUse::StakeForEnergy) => {
+            let _ = campaign_player.recharge_stake_energy(campaign, now_ts)?;
+            campaign_player.spend_stake_energy(mode.energy_cost)?;
+        },
+        (false, false, None, None, None) => {
+            let nft_config = campaign_player.nft_config(campaign)?;
+            let _ = campaign_player.recharge_energy(&nft_config, now_ts)?;
+            campaign_player.spend_energy(mode.energy_cost)?;
+        },
+        (_, _, _, _, _) => return err!(ErrorCodes::InvalidInput),
+    }
     
     campaign_player.game_start_time = now_ts;
+    campaign_player.record_game_day(now_ts);
+    campaign_player.game_mode = game_mode;
+    campaign_player.game_max_rewards = max_rewards;
 
     campaign.active_games.add_assign(1);
     campaign.reserved_rewards.add_assign(max_rewards);
@@ -98,10 +232,11 @@ pub fn start_game(ctx: Context<StartGame>) -> Result<()> {
 }
 
 #[derive(Accounts)]
+#[instruction(game_mode: u8, compressed_nft: Option<CompressedNftProof>)]
 pub struct StartGame<'info> {
-    #[account(mut)]
+    #[account(mut, address = campaign.house @ ErrorCodes::InvalidInput, constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub house: Box<Account<'info, House>>,
-    #[account(mut)]
+    #[account(mut, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub campaign: Box<Account<'info, Campaign>>,
     #[account(mut)]
     pub user: Signer<'info>,
@@ -110,31 +245,39 @@ pub struct StartGame<'info> {
 
     pub system_program: Program<'info, System>,
 
-    #[account(init_if_needed, space=8+CampaignPlayer::INIT_SPACE - campaign.token_config.map_or(StakeInfo::INIT_SPACE, |t| if t.token_use == TokenUse::Stake {0} else {StakeInfo::INIT_SPACE}), 
+    #[account(init_if_needed, space=8+CampaignPlayer::INIT_SPACE - campaign.token_config.map_or(StakeInfo::INIT_SPACE, |t| if t.token_use.is_stake() {0} else {StakeInfo::INIT_SPACE}), 
     seeds = [
         b"player", 
         campaign.key().as_ref(), 
-        &match (&player_nft_metadata, &player_core_nft) {
-            (Some(metadata), _) => metadata.mint.to_bytes(),
-            (None, Some(core_nft)) => core_nft.key().to_bytes(),
-            (None, None) => user.key().to_bytes(),
+        &match (&player_nft_metadata, &player_core_nft, &player_member_mint, &compressed_nft, &merkle_tree) {
+            (Some(metadata), _, _, _, _) => metadata.mint.to_bytes(),
+            (None, Some(core_nft), _, _, _) => core_nft.key().to_bytes(),
+            (None, None, Some(member_mint), _, _) => member_mint.key().to_bytes(),
+            (None, None, None, Some(cnft), Some(tree)) => cnft.asset_id(&tree.key()).to_bytes(),
+            (None, None, None, _, _) => user.key().to_bytes(),
         }[..]
     ],
     bump, 
-    payer = user)]
+    payer = user,
+    constraint = campaign_player.player_identity.identity_type == IdentityType::None || campaign_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub campaign_player: Account<'info, CampaignPlayer>,
 
-    #[account(
-        constraint = player_nft_metadata.as_ref().is_some_and(|m| m.mint == player_nft_token_account.mint ), 
+    #[account(mut,
+        constraint = player_nft_metadata.as_ref().map(|m| m.mint).or(player_member_mint.as_ref().map(|m| m.key())) == Some(player_nft_token_account.mint), 
         constraint = player_nft_token_account.owner == user.key() @ ErrorCodes::TokenOwnerMismatch, 
         constraint = player_nft_token_account.amount == 1 @ ErrorCodes::OwnerBalanceMismatch,
     )]
     pub player_nft_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
 
-    #[account(constraint = metadata_is_collection(&player_nft_metadata,&campaign.nft_config.unwrap().collection).is_ok())]
+    /// validated against the campaign collections in the handler
+    #[account(mut)]
     pub player_nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
 
+    /// CHECK: token record of a programmable NFT, validated against the metadata and token account
+    #[account(mut)]
+    pub player_token_record: Option<AccountInfo<'info>>,
+
     #[account(mut)]
     pub game_deposit_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
     #[account(mut)]
@@ -145,39 +288,231 @@ pub struct StartGame<'info> {
     )]
     pub game_deposit_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    /// CHECK: Custom validation for mpl-core asset
+    /// lamport deposits of campaigns priced in SOL
+    #[account(mut, seeds=[b"sol_deposit", campaign.key().as_ref()], bump)]
+    pub sol_deposit_vault: Option<SystemAccount<'info>>,
+
+    /// CHECK: Custom validation for mpl-core asset, written by mpl-core when the campaign locks it
+    #[account(mut)]
+    pub player_core_nft: Option<AccountInfo<'info>>,
+
+    /// CHECK: Custom validation for mpl-core collection, needed when the campaign collection is its update authority or delegate
+    #[account(mut)]
+    pub player_core_collection: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token-2022 NFT mint, validated through its group member extension
     #[account()]
-    pub player_core_nft: Option<AccountInfo<'info>>
+    pub player_member_mint: Option<AccountInfo<'info>>,
+
+    /// CHECK: Bubblegum tree of a compressed NFT identity, validated against the proof
+    #[account()]
+    pub merkle_tree: Option<AccountInfo<'info>>,
+
+    /// CHECK: campaign proxy signer, required when the campaign locks player NFTs
+    #[account(seeds=[campaign.key().as_ref()], bump)]
+    pub campaign_auth: Option<AccountInfo<'info>>,
+
+    /// CHECK: mint of the player NFT, required to lock it
+    #[account(constraint = player_nft_metadata.as_ref().is_some_and(|m| m.mint == player_nft_mint.key()) @ ErrorCodes::InvalidInput)]
+    pub player_nft_mint: Option<AccountInfo<'info>>,
+
+    /// CHECK: master edition of the player NFT, validated by token metadata
+    #[account()]
+    pub player_nft_edition: Option<AccountInfo<'info>>,
+
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    /// CHECK: instructions sysvar, required to lock token metadata NFTs
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<AccountInfo<'info>>,
+
+    /// CHECK: rule set program of a pNFT, validated by token metadata
+    #[account()]
+    pub authorization_rules_program: Option<AccountInfo<'info>>,
+
+    /// CHECK: rule set of a pNFT, validated by token metadata
+    #[account()]
+    pub authorization_rules: Option<AccountInfo<'info>>,
+
+    /// CHECK: required to lock mpl-core assets
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: Option<AccountInfo<'info>>,
+
+    /// shared energy pool, required when the campaign uses house energy
+    #[account(init_if_needed, space=8+HousePlayer::INIT_SPACE,
+    seeds = [
+        b"house_player",
+        house.key().as_ref(),
+        &match (&player_nft_metadata, &player_core_nft, &player_member_mint, &compressed_nft, &merkle_tree) {
+            (Some(metadata), _, _, _, _) => metadata.mint.to_bytes(),
+            (None, Some(core_nft), _, _, _) => core_nft.key().to_bytes(),
+            (None, None, Some(member_mint), _, _) => member_mint.key().to_bytes(),
+            (None, None, None, Some(cnft), Some(tree)) => cnft.asset_id(&tree.key()).to_bytes(),
+            (None, None, None, _, _) => user.key().to_bytes(),
+        }[..]
+    ],
+    bump,
+    payer = user)]
+    pub house_player: Option<Box<Account<'info, HousePlayer>>>,
+
+    #[account(mut, seeds=[b"energy_mint", campaign.key().as_ref()], bump)]
+    pub energy_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// energy tokens burned to play, required when the campaign energy is tokenized
+    #[account(mut, token::mint = energy_mint, token::authority = user)]
+    pub player_energy_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// campaign reward vault, required when Pay deposits are recycled into the reward pool
+    #[account(mut, token::mint = game_deposit_mint, seeds=[b"rewards", campaign.key().as_ref()], bump)]
+    pub reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// house fee vault, required when Pay deposits are shared with the house
+    #[account(mut, token::mint = game_deposit_mint, seeds=[b"vault", campaign.house.as_ref()], bump)]
+    pub house_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 }
 
 
-impl StartGame<'_> {
-    pub fn validate_core_nft(&self) -> Result<()> {
+impl<'info> StartGame<'info> {
+    /// Freezes the player NFT under campaign_auth so the identity cannot change hands mid-game
+    pub fn lock_player_nft(&self) -> Result<()> {
+        let campaign_key = self.campaign.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[campaign_key.as_ref(), &[self.campaign.auth_bump]]];
+        let campaign_auth = self.campaign_auth.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?;
+        match (&self.player_nft_metadata, &self.player_nft_token_account, &self.player_core_nft, &self.merkle_tree) {
+            (Some(metadata), Some(token_account), None, None) => {
+                let metadata_info = metadata.to_account_info();
+                let token_info = token_account.to_account_info();
+                let system_program_info = self.system_program.to_account_info();
+                let token_program_info = self.token_program.to_account_info();
+                let token_metadata_program = self.token_metadata_program.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?.to_account_info();
+                // an expired game unlocks the NFT but leaves campaign_auth approved
+                let delegated = match validate_token_record(metadata, &token_account.key(), self.player_token_record.as_ref())? {
+                    Some(token_record) => token_record.delegate == Some(campaign_auth.key()),
+                    None => Option::<Pubkey>::from(token_account.delegate) == Some(campaign_auth.key()),
+                };
+                let accounts = NftLockAccounts {
+                    token_metadata_program: &token_metadata_program,
+                    delegate: campaign_auth,
+                    owner: self.user.as_ref(),
+                    payer: self.user.as_ref(),
+                    metadata: &metadata_info,
+                    edition: self.player_nft_edition.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?,
+                    token_record: self.player_token_record.as_ref(),
+                    mint: self.player_nft_mint.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?,
+                    token: &token_info,
+                    system_program: &system_program_info,
+                    sysvar_instructions: self.sysvar_instructions.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?,
+                    token_program: &token_program_info,
+                    authorization_rules_program: self.authorization_rules_program.as_ref(),
+                    authorization_rules: self.authorization_rules.as_ref(),
+                    programmable: is_programmable(metadata),
+                    delegated,
+                };
+                execute_nft_lock(&accounts, signer_seeds)
+            },
+            (None, None, Some(core_nft), None) => {
+                let mpl_core_program = self.mpl_core_program.as_ref().ok_or(ErrorCodes::NftLockAccountsRequired)?;
+                let delegated = CoreAsset::from_account_info(core_nft)?.plugins.is_freeze_delegate(&campaign_auth.key());
+                execute_core_freeze(
+                    core_nft.to_account_info(),
+                    self.player_core_collection.clone(),
+                    self.user.to_account_info(),
+                    campaign_auth.to_account_info(),
+                    self.system_program.to_account_info(),
+                    mpl_core_program.to_account_info(),
+                    delegated,
+                    signer_seeds)
+            },
+            (None, None, None, None) => Ok(()), // tokenized energy holder, nothing to lock
+            (_, _, _, _) => err!(ErrorCodes::NftLockUnsupported),
+        }
+    }
+    /// Each validator returns the index of the campaign collection the presented NFT belongs to
+    pub fn validate_metadata_nft(&self) -> Result<Option<u8>> {
+        if let Some(metadata) = &self.player_nft_metadata {
+            let nft_config = self.campaign.nft_config.ok_or(ErrorCodes::UnexpectedMetadata)?;
+            let index = self.campaign.find_collection(|c| metadata_is_collection(metadata, c, nft_config.collection_verification).is_ok())?;
+            return Ok(Some(index));
+        }
+        Ok(None)
+    }
+    pub fn validate_core_nft(&self) -> Result<Option<u8>> {
         if let Some(core_nft_info) = &self.player_core_nft {
-            let nft = SimplifiedAssetV1::from_account_info(core_nft_info)?;
-            
-            if nft.owner != self.user.key() {
-                return err!(ErrorCodes::TokenOwnerMismatch);
-            }
-            
-            if let Some(nft_config) = self.campaign.nft_config {
-                if nft.update_authority != UpdateAuthority::Collection(nft_config.collection) {
-                    return err!(ErrorCodes::OwnerBalanceMismatch);
-                }
-            } else {
-                return err!(ErrorCodes::InvalidInput);
-            }
+            let (nft, collection) = load_owned_core_asset(core_nft_info, self.player_core_collection.as_ref(), &self.user.key())?;
+            nft.ensure_unlocked(collection.as_ref())?;
+            let index = self.campaign.find_collection(|c| nft.verify_collection(c, collection.as_ref()).is_ok())?;
+            return Ok(Some(index));
+        }
+        Ok(None)
+    }
+    pub fn validate_programmable_nft(&self) -> Result<()> {
+        if let (Some(metadata), Some(token_account)) = (&self.player_nft_metadata, &self.player_nft_token_account) {
+            validate_token_record(metadata, &token_account.key(), self.player_token_record.as_ref())?;
         }
-        
         Ok(())
     }
-    pub fn get_player_identity(&self) -> Result<PlayerIdentity> {
+    pub fn validate_member_nft(&self) -> Result<Option<u8>> {
+        if let Some(member_mint) = &self.player_member_mint {
+            require!(self.player_nft_token_account.is_some(), ErrorCodes::InvalidInput);
+            let index = self.campaign.find_collection(|c| verify_group_member(member_mint, c).is_ok())?;
+            return Ok(Some(index));
+        }
+        Ok(None)
+    }
+    pub fn validate_compressed_nft(&self, compressed_nft: &Option<CompressedNftProof>, remaining_accounts: &[AccountInfo]) -> Result<Option<u8>> {
+        match (compressed_nft, &self.merkle_tree) {
+            (Some(proof), Some(merkle_tree)) => {
+                let nft_config = self.campaign.nft_config.ok_or(ErrorCodes::InvalidInput)?;
+                let verification = nft_config.collection_verification;
+                let index = self.campaign.find_collection(|c| proof.metadata.is_collection(c, verification))?;
+                let collection = self.campaign.get_collection(index)?.collection;
+                proof.verify(merkle_tree, &self.user.key(), &collection, verification, remaining_accounts)?;
+                Ok(Some(index))
+            },
+            (None, None) => Ok(None),
+            (_, _) => err!(ErrorCodes::InvalidInput),
+        }
+    }
+
+    /// Core assets carry their traits in the Attributes plugin, other NFTs prove them against the campaign trait root
+    pub fn get_trait_bonus(&self, identity: &PlayerIdentity, trait_proof: &Option<TraitProof>) -> Result<TraitBonus> {
+        let multipliers = &self.campaign.trait_multipliers;
+        if multipliers.is_empty() {
+            return Ok(TraitBonus::default());
+        }
+        match (&self.player_core_nft, trait_proof, self.campaign.nft_config.and_then(|c| c.trait_root)) {
+            (Some(core_nft_info), None, _) => {
+                let nft = CoreAsset::from_account_info(core_nft_info)?;
+                Ok(TraitBonus::from_traits(multipliers, &nft.plugins.attributes))
+            },
+            (None, Some(proof), Some(root)) => {
+                proof.verify(&root, &identity.pubkey)?;
+                Ok(TraitBonus::from_traits(multipliers, &proof.traits))
+            },
+            (_, None, _) => Ok(TraitBonus::default()),
+            (_, Some(_), _) => err!(ErrorCodes::TraitProofInvalid),
+        }
+    }
+
+    pub fn get_player_identity(&self, compressed_nft: &Option<CompressedNftProof>) -> Result<PlayerIdentity> {
         match (self.campaign.nft_config, &self.player_nft_metadata, &self.player_core_nft) {
             (None, None, None) => Ok(PlayerIdentity{
                     identity_type: IdentityType::User,
                     pubkey: self.user.key(),
                 }),
             (None, Some(_), None) => err!(ErrorCodes::UnexpectedMetadata),
+            (Some(_), None, None) if self.player_member_mint.is_some() => Ok(PlayerIdentity{
+                    identity_type: IdentityType::Token2022Nft,
+                    pubkey: self.player_member_mint.as_ref().unwrap().key(),
+                }),
+            (Some(_), None, None) if compressed_nft.is_some() && self.merkle_tree.is_some() => Ok(PlayerIdentity{
+                    identity_type: IdentityType::CompressedNft,
+                    pubkey: compressed_nft.as_ref().unwrap().asset_id(&self.merkle_tree.as_ref().unwrap().key()),
+                }),
+            (Some(nft_config), None, None) if nft_config.tokenized_energy => Ok(PlayerIdentity{
+                    identity_type: IdentityType::User,
+                    pubkey: self.user.key(),
+                }),
             (Some(_), None, None) => err!(ErrorCodes::MissingMetadata),
             (Some(_), Some(metadata), None) => Ok(PlayerIdentity{
              identity_type: IdentityType::Nft,
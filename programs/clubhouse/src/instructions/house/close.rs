@@ -1,11 +1,11 @@
 use anchor_lang::prelude::*;
 
-use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface}};
 
-use crate::{execute_token_close, execute_token_transfer, state::House};
+use crate::{errors::ErrorCodes, execute_token_close, execute_token_transfer, state::House, ACCOUNT_VERSION};
 
 
-pub fn close_house(ctx: Context<crate::CloseHouse>) -> Result<()> {
+pub fn close_house<'info>(ctx: Context<'_, '_, '_, 'info, crate::CloseHouse<'info>>) -> Result<()> {
     let house = &mut ctx.accounts.house;
     if house.open_campaigns > 0 {
         return err!(crate::errors::ErrorCodes::ActiveCampaigns);
@@ -14,11 +14,14 @@ pub fn close_house(ctx: Context<crate::CloseHouse>) -> Result<()> {
     let vault = &ctx.accounts.house_currency_vault;
     if vault.amount > 0 {
         execute_token_transfer(vault.amount,
+            house.house_currency_decimals,
             ctx.accounts.house_currency_vault.to_account_info(), 
+            ctx.accounts.house_currency.to_account_info(),
             ctx.accounts.admin_withdraw_account.to_account_info(),
             house.to_account_info(), 
             ctx.accounts.token_program.to_account_info(), 
-        Some(&[&[b"house",&house.house_name.as_bytes()[..], &[house.bump][..]]]))?;
+        Some(&[&[b"house",&house.house_name.as_bytes()[..], &[house.bump][..]]]),
+        ctx.remaining_accounts)?;
     }
 
     execute_token_close(vault.to_account_info(), 
@@ -31,7 +34,7 @@ pub fn close_house(ctx: Context<crate::CloseHouse>) -> Result<()> {
 
 #[derive(Accounts)]
 pub struct CloseHouse<'info> {
-    #[account(mut, close=house_admin, has_one=house_admin, has_one=house_currency)]
+    #[account(mut, close=house_admin, has_one=house_admin, has_one=house_currency, constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub house: Box<Account<'info, House>>,
     #[account(mut)]
     pub house_admin: Signer<'info>,
@@ -42,17 +45,18 @@ pub struct CloseHouse<'info> {
         bump,
         token::token_program = token_program
     )]
-    pub house_currency_vault: Account<'info, TokenAccount>,
+    pub house_currency_vault: InterfaceAccount<'info, TokenAccount>,
 
     #[account(init_if_needed, payer=house_admin,
         associated_token::authority = house_admin,
         associated_token::mint = house_currency,
+        associated_token::token_program = token_program,
     )]
-    pub admin_withdraw_account: Account<'info, TokenAccount>,
+    pub admin_withdraw_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub house_currency: Account<'info, Mint>,
+    pub house_currency: InterfaceAccount<'info, Mint>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
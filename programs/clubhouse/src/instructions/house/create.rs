@@ -1,17 +1,19 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::{House, HouseConfig};
+use crate::{CollectionVerification, EnergyConfig, House, HouseConfig};
 
 
-pub fn create_house(ctx: Context<CreateHouse>, manager_collection: Option<Pubkey>, house_config: HouseConfig, house_name: String, uri: Option<String>) -> Result<()> {
+pub fn create_house(ctx: Context<CreateHouse>, manager_collection: Option<Pubkey>, house_config: HouseConfig, house_name: String, uri: Option<String>, player_energy: Option<EnergyConfig>, manager_collection_verification: CollectionVerification) -> Result<()> {
     
     ctx.accounts.house.initialize(
         ctx.accounts.house_admin.key(),
         manager_collection,
+        manager_collection_verification,
         ctx.accounts.house_currency_mint.key(),
         ctx.accounts.house_currency_mint.decimals,
         house_config,
+        player_energy,
         house_name,
         uri,
         ctx.bumps.house,
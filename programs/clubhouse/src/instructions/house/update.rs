@@ -1,18 +1,22 @@
 use anchor_lang::prelude::*;
 
-use crate::state::{HouseConfig, House};
+use crate::{errors::ErrorCodes, state::{EnergyConfig, HouseConfig, House}, ACCOUNT_VERSION};
 
 
-pub fn update_house(ctx: Context<UpdateHouse>, house_config: HouseConfig, uri: Option<String>) -> Result<()> {
+pub fn update_house(ctx: Context<UpdateHouse>, house_config: HouseConfig, uri: Option<String>, player_energy: Option<EnergyConfig>) -> Result<()> {
+    if let Some(energy_config) = player_energy {
+        energy_config.validate()?;
+    }
     ctx.accounts.house.config = house_config;
     ctx.accounts.house.uri = uri;
+    ctx.accounts.house.player_energy = player_energy;
     Ok(())
 }
 
 
 #[derive(Accounts)]
 pub struct UpdateHouse<'info> {
-    #[account(mut, has_one=house_admin)]
+    #[account(mut, has_one=house_admin, constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub house: Box<Account<'info, House>>,
     pub house_admin: Signer<'info>,
 }
@@ -2,19 +2,22 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface}};
 
-use crate::{execute_token_transfer, House};
+use crate::{errors::ErrorCodes, execute_token_transfer, House, ACCOUNT_VERSION};
 
 
-pub fn withdraw_house_fees(ctx: Context<WithdrawHouseFees>) -> Result<()> {
+pub fn withdraw_house_fees<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawHouseFees<'info>>) -> Result<()> {
     let house = &mut ctx.accounts.house;
     let vault = &ctx.accounts.house_currency_vault;
     if vault.amount > 0 {
         execute_token_transfer(vault.amount,
+            house.house_currency_decimals,
             ctx.accounts.house_currency_vault.to_account_info(),
+            ctx.accounts.house_currency.to_account_info(),
             ctx.accounts.admin_withdraw_account.to_account_info(), 
             house.to_account_info(), 
             ctx.accounts.token_program.to_account_info(), 
-        Some(&[&[b"house",&house.house_name.as_bytes()[..], &[house.bump][..]]]))?;
+        Some(&[&[b"house",&house.house_name.as_bytes()[..], &[house.bump][..]]]),
+        ctx.remaining_accounts)?;
     }
 
     let house_rent = Rent::minimum_balance(&Rent::get().unwrap(), 500);
@@ -32,7 +35,7 @@ pub fn withdraw_house_fees(ctx: Context<WithdrawHouseFees>) -> Result<()> {
 
 #[derive(Accounts)]
 pub struct WithdrawHouseFees<'info> {
-    #[account(mut,has_one=house_admin, has_one=house_currency)]
+    #[account(mut,has_one=house_admin, has_one=house_currency, constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub house: Box<Account<'info, House>>,
     #[account(mut)]
     pub house_admin: Signer<'info>,
@@ -48,6 +51,7 @@ pub struct WithdrawHouseFees<'info> {
     #[account(init_if_needed, payer=house_admin,
         associated_token::authority = house_admin,
         associated_token::mint = house_currency,
+        associated_token::token_program = token_program,
     )]
     pub admin_withdraw_account: InterfaceAccount<'info, TokenAccount>,
 
@@ -4,28 +4,70 @@ pub mod campaign;
 pub mod game;
 pub mod player;
 
-use anchor_lang::{prelude::*, system_program};
-use anchor_spl::token_interface::{self, Transfer};
+use anchor_lang::{prelude::*, solana_program::program::{invoke, invoke_signed}, system_program};
+use anchor_spl::metadata::mpl_token_metadata::instructions::{
+    DelegateStakingV1CpiBuilder, DelegateStandardV1CpiBuilder, LockV1CpiBuilder,
+    RevokeStakingV1CpiBuilder, RevokeStandardV1CpiBuilder, UnlockV1CpiBuilder,
+};
+use anchor_spl::{token_2022::{spl_token_2022::{self, extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions}}, Token2022}, token_interface::{self, TransferChecked}};
 
+use crate::{errors::ErrorCodes, freeze_asset_instruction, remove_freeze_instruction, update_freeze_instruction};
 
+
+/// Hook-enabled Token-2022 mints need their extra-account-metas in remaining_accounts,
+/// they are resolved by key so unrelated accounts are ignored
 pub fn execute_token_transfer<'a>(
     amount: u64,
+    decimals: u8,
     from: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
     to: AccountInfo<'a>,
     authority: AccountInfo<'a>,
     token_program: AccountInfo<'a>,
-    signer_seeds: Option<&[&[&[u8]]]>
+    signer_seeds: Option<&[&[&[u8]]]>,
+    remaining_accounts: &[AccountInfo<'a>],
 ) -> Result<()>{
-    let accounts = anchor_spl::token_interface::Transfer {
+    if !remaining_accounts.is_empty() {
+        spl_token_2022::onchain::invoke_transfer_checked(
+            token_program.key,
+            from,
+            mint,
+            to,
+            authority,
+            remaining_accounts,
+            amount,
+            decimals,
+            signer_seeds.unwrap_or(&[]),
+        )?;
+        return Ok(());
+    }
+    let accounts = anchor_spl::token_interface::TransferChecked {
         from,
+        mint,
         to,
         authority
     };
-    let ctx: CpiContext<'_, '_, '_, '_, Transfer<'_>> = CpiContext::new(token_program, accounts);
-    token_interface::transfer(match signer_seeds {
+    let ctx: CpiContext<'_, '_, '_, '_, TransferChecked<'_>> = CpiContext::new(token_program, accounts);
+    token_interface::transfer_checked(match signer_seeds {
         Some(seeds) => ctx.with_signer(seeds),
         None => ctx,
-    }, amount)
+    }, amount, decimals)
+}
+
+/// Amount that reaches the destination once the Token-2022 transfer fee of the mint is withheld
+pub fn get_amount_after_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint.owner != Token2022::id() {
+        return Ok(amount);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let fee = match mint_state.get_extension::<TransferFeeConfig>() {
+        Ok(fee_config) => fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(ErrorCodes::InvalidInput)?,
+        Err(_) => 0,
+    };
+    Ok(amount.saturating_sub(fee))
 }
 
 pub fn execute_lamport_transfer<'a>(
@@ -84,3 +126,211 @@ pub fn execute_token_burn<'a>(
         None => ctx
     }, amount)
 }
+
+pub fn execute_token_mint<'a>(
+    amount: u64,
+    mint: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    signer_seeds: Option<&[&[&[u8]]]>
+) -> Result<()> {
+    let accounts = anchor_spl::token_interface::MintTo {
+        mint,
+        to,
+        authority
+    };
+    let ctx = CpiContext::new(token_program, accounts);
+    token_interface::mint_to(match signer_seeds {
+        Some(seeds) => ctx.with_signer(seeds),
+        None => ctx
+    }, amount)
+}
+
+/// Token Metadata accounts needed to lock or unlock a legacy or programmable NFT
+pub struct NftLockAccounts<'b, 'a> {
+    pub token_metadata_program: &'b AccountInfo<'a>,
+    pub delegate: &'b AccountInfo<'a>,
+    pub owner: &'b AccountInfo<'a>,
+    /// the owner in start_game and end_game, whoever expires the game otherwise
+    pub payer: &'b AccountInfo<'a>,
+    pub metadata: &'b AccountInfo<'a>,
+    pub edition: &'b AccountInfo<'a>,
+    pub token_record: Option<&'b AccountInfo<'a>>,
+    pub mint: &'b AccountInfo<'a>,
+    pub token: &'b AccountInfo<'a>,
+    pub system_program: &'b AccountInfo<'a>,
+    pub sysvar_instructions: &'b AccountInfo<'a>,
+    pub token_program: &'b AccountInfo<'a>,
+    pub authorization_rules_program: Option<&'b AccountInfo<'a>>,
+    pub authorization_rules: Option<&'b AccountInfo<'a>>,
+    pub programmable: bool,
+    /// `delegate` is still approved from a game that was expired rather than ended
+    pub delegated: bool,
+}
+
+/// The owner approves `delegate` (staking delegate for pNFTs, standard delegate otherwise)
+/// and the delegate locks the NFT, which freezes the token account
+pub fn execute_nft_lock(accounts: &NftLockAccounts, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    if accounts.delegated {
+        // the approval survived expire_game, only the lock has to be taken again
+    } else if accounts.programmable {
+        DelegateStakingV1CpiBuilder::new(accounts.token_metadata_program)
+            .delegate(accounts.delegate)
+            .metadata(accounts.metadata)
+            .master_edition(Some(accounts.edition))
+            .token_record(accounts.token_record)
+            .mint(accounts.mint)
+            .token(accounts.token)
+            .authority(accounts.owner)
+            .payer(accounts.owner)
+            .system_program(accounts.system_program)
+            .sysvar_instructions(accounts.sysvar_instructions)
+            .spl_token_program(Some(accounts.token_program))
+            .authorization_rules_program(accounts.authorization_rules_program)
+            .authorization_rules(accounts.authorization_rules)
+            .amount(1)
+            .invoke()?;
+    } else {
+        DelegateStandardV1CpiBuilder::new(accounts.token_metadata_program)
+            .delegate(accounts.delegate)
+            .metadata(accounts.metadata)
+            .master_edition(Some(accounts.edition))
+            .mint(accounts.mint)
+            .token(accounts.token)
+            .authority(accounts.owner)
+            .payer(accounts.owner)
+            .system_program(accounts.system_program)
+            .sysvar_instructions(accounts.sysvar_instructions)
+            .spl_token_program(Some(accounts.token_program))
+            .amount(1)
+            .invoke()?;
+    }
+    LockV1CpiBuilder::new(accounts.token_metadata_program)
+        .authority(accounts.delegate)
+        .token_owner(Some(accounts.owner))
+        .token(accounts.token)
+        .mint(accounts.mint)
+        .metadata(accounts.metadata)
+        .edition(Some(accounts.edition))
+        .token_record(accounts.token_record)
+        .payer(accounts.payer)
+        .system_program(accounts.system_program)
+        .sysvar_instructions(accounts.sysvar_instructions)
+        .spl_token_program(Some(accounts.token_program))
+        .authorization_rules_program(accounts.authorization_rules_program)
+        .authorization_rules(accounts.authorization_rules)
+        .invoke_signed(signer_seeds)?;
+    Ok(())
+}
+
+/// Reverses the lock of `execute_nft_lock`, signed by the delegate alone
+pub fn execute_nft_unlock(accounts: &NftLockAccounts, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    UnlockV1CpiBuilder::new(accounts.token_metadata_program)
+        .authority(accounts.delegate)
+        .token_owner(Some(accounts.owner))
+        .token(accounts.token)
+        .mint(accounts.mint)
+        .metadata(accounts.metadata)
+        .edition(Some(accounts.edition))
+        .token_record(accounts.token_record)
+        .payer(accounts.payer)
+        .system_program(accounts.system_program)
+        .sysvar_instructions(accounts.sysvar_instructions)
+        .spl_token_program(Some(accounts.token_program))
+        .authorization_rules_program(accounts.authorization_rules_program)
+        .authorization_rules(accounts.authorization_rules)
+        .invoke_signed(signer_seeds)?;
+    Ok(())
+}
+
+/// The owner revokes the delegate approved by `execute_nft_lock`
+pub fn execute_nft_revoke(accounts: &NftLockAccounts) -> Result<()> {
+    if accounts.programmable {
+        RevokeStakingV1CpiBuilder::new(accounts.token_metadata_program)
+            .delegate(accounts.delegate)
+            .metadata(accounts.metadata)
+            .master_edition(Some(accounts.edition))
+            .token_record(accounts.token_record)
+            .mint(accounts.mint)
+            .token(accounts.token)
+            .authority(accounts.owner)
+            .payer(accounts.owner)
+            .system_program(accounts.system_program)
+            .sysvar_instructions(accounts.sysvar_instructions)
+            .spl_token_program(Some(accounts.token_program))
+            .authorization_rules_program(accounts.authorization_rules_program)
+            .authorization_rules(accounts.authorization_rules)
+            .invoke()?;
+    } else {
+        RevokeStandardV1CpiBuilder::new(accounts.token_metadata_program)
+            .delegate(accounts.delegate)
+            .metadata(accounts.metadata)
+            .master_edition(Some(accounts.edition))
+            .mint(accounts.mint)
+            .token(accounts.token)
+            .authority(accounts.owner)
+            .payer(accounts.owner)
+            .system_program(accounts.system_program)
+            .sysvar_instructions(accounts.sysvar_instructions)
+            .spl_token_program(Some(accounts.token_program))
+            .invoke()?;
+    }
+    Ok(())
+}
+
+/// Freezes an mpl-core asset under `delegate` through a FreezeDelegate plugin, signed by the owner.
+/// A plugin the delegate kept from an expired game is frozen again by the delegate instead
+pub fn execute_core_freeze<'a>(
+    asset: AccountInfo<'a>,
+    collection: Option<AccountInfo<'a>>,
+    owner: AccountInfo<'a>,
+    delegate: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    core_program: AccountInfo<'a>,
+    delegated: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let collection_key = collection.as_ref().map(|c| c.key());
+    let collection = collection.unwrap_or(core_program.clone());
+    if delegated {
+        let ix = update_freeze_instruction(asset.key, collection_key.as_ref(), owner.key, delegate.key, true);
+        invoke_signed(&ix, &[asset, collection, owner, delegate, system_program, core_program], signer_seeds)?;
+    } else {
+        let ix = freeze_asset_instruction(asset.key, collection_key.as_ref(), owner.key, delegate.key);
+        invoke(&ix, &[asset, collection, owner, system_program, core_program])?;
+    }
+    Ok(())
+}
+
+/// Thaws the asset with the delegate, the FreezeDelegate plugin stays in place
+pub fn execute_core_thaw<'a>(
+    asset: AccountInfo<'a>,
+    collection: Option<AccountInfo<'a>>,
+    payer: AccountInfo<'a>,
+    delegate: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    core_program: AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let collection_key = collection.as_ref().map(|c| c.key());
+    let collection = collection.unwrap_or(core_program.clone());
+    let thaw = update_freeze_instruction(asset.key, collection_key.as_ref(), payer.key, delegate.key, false);
+    invoke_signed(&thaw, &[asset, collection, payer, delegate, system_program, core_program], signer_seeds)?;
+    Ok(())
+}
+
+/// Removes the thawed FreezeDelegate plugin, signed by the owner
+pub fn execute_core_remove_freeze<'a>(
+    asset: AccountInfo<'a>,
+    collection: Option<AccountInfo<'a>>,
+    owner: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    core_program: AccountInfo<'a>,
+) -> Result<()> {
+    let collection_key = collection.as_ref().map(|c| c.key());
+    let collection = collection.unwrap_or(core_program.clone());
+    let remove = remove_freeze_instruction(asset.key, collection_key.as_ref(), owner.key);
+    invoke(&remove, &[asset, collection, owner, system_program, core_program])?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::ops::AddAssign;
+
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, metadata::MetadataAccount, token_interface::{Mint, TokenAccount, TokenInterface}};
+
+use crate::{errors::ErrorCodes, execute_token_mint, metadata_is_collection, state::{load_owned_core_asset, CampaignPlayer}, verify_group_member, Campaign, CompressedNftProof, House, IdentityType, PlayerIdentity, StakeInfo, ACCOUNT_VERSION};
+
+pub fn claim_energy<'info>(ctx: Context<'_, '_, '_, 'info, ClaimEnergy<'info>>, compressed_nft: Option<CompressedNftProof>) -> Result<()> {
+    let collection_index = [
+        ctx.accounts.validate_metadata_nft()?,
+        ctx.accounts.validate_core_nft()?,
+        ctx.accounts.validate_member_nft()?,
+        ctx.accounts.validate_compressed_nft(&compressed_nft, ctx.remaining_accounts)?,
+    ].into_iter().flatten().next().unwrap_or(0);
+    let inferred_identity = ctx.accounts.get_player_identity(&compressed_nft)?;
+    require!(ctx.accounts.campaign.nft_config.is_some_and(|c| c.tokenized_energy), ErrorCodes::EnergyNotTokenized);
+    require!(ctx.accounts.campaign.nft_config.is_some_and(|c| c.allows_identity(inferred_identity.identity_type)), ErrorCodes::IdentityTypeNotAllowed);
+    let campaign = &mut ctx.accounts.campaign;
+    let campaign_player = &mut ctx.accounts.campaign_player;
+
+    if campaign_player.player_identity.identity_type == IdentityType::None {
+        //new or reinitialized campaign player
+        campaign_player.set_inner(CampaignPlayer::new(inferred_identity, &campaign, collection_index)?);
+        ctx.accounts.house.unique_players.add_assign(1);
+        campaign.player_count.add_assign(1);
+    }
+
+    require!(campaign_player.player_identity == inferred_identity, ErrorCodes::PlayerIdentityMismatch);
+
+    let now_ts = Clock::get()?.unix_timestamp;
+    require!(!campaign.time_span.is_expired(now_ts), ErrorCodes::CampaignExpired);
+
+    let nft_config = campaign_player.nft_config(campaign)?.ok_or(ErrorCodes::EnergyNotTokenized)?;
+    let energy = campaign_player.withdraw_energy(&nft_config, now_ts)?;
+    execute_token_mint(
+        energy.into(),
+        ctx.accounts.energy_mint.to_account_info(),
+        ctx.accounts.player_energy_account.to_account_info(),
+        ctx.accounts.campaign_auth.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        Some(&[&[campaign.key().as_ref(), &[campaign.auth_bump]]]))?;
+    msg!("energy claimed as tokens: {}", energy);
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(compressed_nft: Option<CompressedNftProof>)]
+pub struct ClaimEnergy<'info> {
+    #[account(mut, constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub house: Box<Account<'info, House>>,
+
+    #[account(mut, has_one=house, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+
+    /// CHECK: campaign proxy signer
+    #[account(seeds=[campaign.key().as_ref()], bump)]
+    pub campaign_auth: AccountInfo<'info>,
+
+    #[account(init_if_needed, space=8+CampaignPlayer::INIT_SPACE-StakeInfo::INIT_SPACE,
+    seeds = [
+        b"player",
+        campaign.key().as_ref(),
+        &match (&player_nft_metadata, &player_core_nft, &player_member_mint, &compressed_nft, &merkle_tree) {
+            (Some(metadata), _, _, _, _) => metadata.mint.to_bytes(),
+            (None, Some(core_nft), _, _, _) => core_nft.key().to_bytes(),
+            (None, None, Some(member_mint), _, _) => member_mint.key().to_bytes(),
+            (None, None, None, Some(cnft), Some(tree)) => cnft.asset_id(&tree.key()).to_bytes(),
+            (None, None, None, _, _) => user.key().to_bytes(),
+        }[..]
+    ],
+    bump,
+    payer = user,
+    constraint = campaign_player.player_identity.identity_type == IdentityType::None || campaign_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign_player: Box<Account<'info, CampaignPlayer>>,
+
+    #[account(
+        constraint = player_nft_metadata.as_ref().map(|m| m.mint).or(player_member_mint.as_ref().map(|m| m.key())) == Some(player_nft_token_account.mint),
+        constraint = player_nft_token_account.owner == user.key() @ ErrorCodes::TokenOwnerMismatch,
+        constraint = player_nft_token_account.amount == 1 @ ErrorCodes::OwnerBalanceMismatch,
+    )]
+    pub player_nft_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// validated against the campaign collections in the handler
+    pub player_nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    /// CHECK: Custom validation for mpl-core asset
+    #[account()]
+    pub player_core_nft: Option<AccountInfo<'info>>,
+
+    /// CHECK: Custom validation for mpl-core collection, needed when the campaign collection is its update authority or delegate
+    #[account()]
+    pub player_core_collection: Option<AccountInfo<'info>>,
+
+    /// CHECK: Token-2022 NFT mint, validated through its group member extension
+    #[account()]
+    pub player_member_mint: Option<AccountInfo<'info>>,
+
+    /// CHECK: Bubblegum tree of a compressed NFT identity, validated against the proof
+    #[account()]
+    pub merkle_tree: Option<AccountInfo<'info>>,
+
+    #[account(mut, seeds=[b"energy_mint", campaign.key().as_ref()], bump)]
+    pub energy_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(init_if_needed, payer=user,
+        associated_token::mint = energy_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub player_energy_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl ClaimEnergy<'_> {
+    /// Each validator returns the index of the campaign collection the presented NFT belongs to
+    pub fn validate_metadata_nft(&self) -> Result<Option<u8>> {
+        if let Some(metadata) = &self.player_nft_metadata {
+            // ownership is only checked through the token account constraints
+            require!(self.player_nft_token_account.is_some(), ErrorCodes::InvalidInput);
+            let nft_config = self.campaign.nft_config.ok_or(ErrorCodes::UnexpectedMetadata)?;
+            let index = self.campaign.find_collection(|c| metadata_is_collection(metadata, c, nft_config.collection_verification).is_ok())?;
+            return Ok(Some(index));
+        }
+        Ok(None)
+    }
+    pub fn validate_core_nft(&self) -> Result<Option<u8>> {
+        if let Some(core_nft_info) = &self.player_core_nft {
+            let (nft, collection) = load_owned_core_asset(core_nft_info, self.player_core_collection.as_ref(), &self.user.key())?;
+            nft.ensure_unlocked(collection.as_ref())?;
+            let index = self.campaign.find_collection(|c| nft.verify_collection(c, collection.as_ref()).is_ok())?;
+            return Ok(Some(index));
+        }
+        Ok(None)
+    }
+    pub fn validate_member_nft(&self) -> Result<Option<u8>> {
+        if let Some(member_mint) = &self.player_member_mint {
+            require!(self.player_nft_token_account.is_some(), ErrorCodes::InvalidInput);
+            let index = self.campaign.find_collection(|c| verify_group_member(member_mint, c).is_ok())?;
+            return Ok(Some(index));
+        }
+        Ok(None)
+    }
+    pub fn validate_compressed_nft(&self, compressed_nft: &Option<CompressedNftProof>, remaining_accounts: &[AccountInfo]) -> Result<Option<u8>> {
+        match (compressed_nft, &self.merkle_tree) {
+            (Some(proof), Some(merkle_tree)) => {
+                let nft_config = self.campaign.nft_config.ok_or(ErrorCodes::InvalidInput)?;
+                let verification = nft_config.collection_verification;
+                let index = self.campaign.find_collection(|c| proof.metadata.is_collection(c, verification))?;
+                let collection = self.campaign.get_collection(index)?.collection;
+                proof.verify(merkle_tree, &self.user.key(), &collection, verification, remaining_accounts)?;
+                Ok(Some(index))
+            },
+            (None, None) => Ok(None),
+            (_, _) => err!(ErrorCodes::InvalidInput),
+        }
+    }
+
+    /// Only NFT holders accrue energy, token holders without an NFT can only spend it
+    pub fn get_player_identity(&self, compressed_nft: &Option<CompressedNftProof>) -> Result<PlayerIdentity> {
+        match (&self.player_nft_metadata, &self.player_core_nft, &self.player_member_mint, compressed_nft, &self.merkle_tree) {
+            (Some(metadata), None, None, None, None) => Ok(PlayerIdentity{
+                identity_type: IdentityType::Nft,
+                pubkey: metadata.mint
+            }),
+            (None, Some(nft), None, None, None) => Ok(PlayerIdentity{
+                identity_type: IdentityType::MplCore,
+                pubkey: nft.key()
+            }),
+            (None, None, Some(member_mint), None, None) => Ok(PlayerIdentity{
+                identity_type: IdentityType::Token2022Nft,
+                pubkey: member_mint.key()
+            }),
+            (None, None, None, Some(proof), Some(merkle_tree)) => Ok(PlayerIdentity{
+                identity_type: IdentityType::CompressedNft,
+                pubkey: proof.asset_id(merkle_tree.key)
+            }),
+            (None, None, None, None, None) => err!(ErrorCodes::MissingMetadata),
+            (_, _, _, _, _) => err!(ErrorCodes::InvalidInput),
+        }
+    }
+}
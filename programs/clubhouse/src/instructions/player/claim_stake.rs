@@ -1,23 +1,70 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface}};
 
-use crate::{errors::ErrorCodes, execute_token_transfer, state::CampaignPlayer, StakeInfo};
+use crate::{errors::ErrorCodes, execute_lamport_transfer, execute_token_transfer, state::CampaignPlayer, Campaign, StakeInfo, ACCOUNT_VERSION};
 
-pub fn claim_stake(ctx: Context<ClaimStake>) -> Result<()> {
+pub fn claim_stake<'info>(ctx: Context<'_, '_, '_, 'info, ClaimStake<'info>>) -> Result<()> {
     require!(ctx.accounts.campaign_player.stake_info.is_some(), ErrorCodes::NoStake);
     
     let campaign = ctx.accounts.campaign_player.campaign;
 
     match ctx.accounts.campaign_player.stake_info.as_mut() {
         Some(stake_info) => {
-            require!(stake_info.campaign_end_time < Clock::get()?.unix_timestamp, ErrorCodes::ActiveCampaign);
-            execute_token_transfer(
-                stake_info.amount,
-                ctx.accounts.game_deposit_vault.to_account_info(),
-                ctx.accounts.stake_recipient_account.to_account_info(),
-                ctx.accounts.campaign_auth.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                Some(&[&[campaign.as_ref(), &[ctx.bumps.campaign_auth]]]))?;
+            let now_ts = Clock::get()?.unix_timestamp;
+            require!(stake_info.campaign_end_time < now_ts, ErrorCodes::ActiveCampaign);
+            match (&ctx.accounts.game_deposit_vault, &ctx.accounts.game_deposit_mint, &ctx.accounts.stake_recipient_account, &ctx.accounts.token_program, &ctx.accounts.sol_deposit_vault) {
+                (Some(game_deposit_vault), Some(game_deposit_mint), Some(stake_recipient_account), Some(token_program), None) => {
+                    execute_token_transfer(
+                        stake_info.amount,
+                        stake_info.staked_mint_decimals,
+                        game_deposit_vault.to_account_info(),
+                        game_deposit_mint.to_account_info(),
+                        stake_recipient_account.to_account_info(),
+                        ctx.accounts.campaign_auth.to_account_info(),
+                        token_program.to_account_info(),
+                        Some(&[&[campaign.as_ref(), &[ctx.bumps.campaign_auth]]]),
+                        ctx.remaining_accounts)?;
+                },
+                (None, None, None, None, Some(sol_deposit_vault)) if stake_info.staked_mint == System::id() => {
+                    execute_lamport_transfer(
+                        stake_info.amount,
+                        sol_deposit_vault.to_account_info(),
+                        ctx.accounts.user.to_account_info(),
+                        ctx.accounts.system_program.to_account_info(),
+                        Some(&[&[b"sol_deposit", campaign.as_ref(), &[ctx.bumps.sol_deposit_vault.unwrap()]]]))?;
+                },
+                (_, _, _, _, _) => return err!(ErrorCodes::InvalidInput),
+            }
+
+            // the campaign may already be closed, the vault stays escrowed under campaign_auth
+            let campaign_info = ctx.accounts.campaign.to_account_info();
+            if campaign_info.owner == &crate::id() && !campaign_info.data_is_empty() {
+                let mut campaign_account = Campaign::try_deserialize(&mut &campaign_info.try_borrow_data()?[..])?;
+                require!(campaign_account.version == ACCOUNT_VERSION, ErrorCodes::AccountNotMigrated);
+                let amount = stake_info.amount;
+                campaign_account.remove_stake(stake_info, amount, now_ts)?;
+                campaign_account.try_serialize(&mut &mut campaign_info.try_borrow_mut_data()?[..])?;
+
+                // the reward vault closes with the campaign, settled yield is paid while it exists
+                if stake_info.yield_pending > 0 {
+                    match (&ctx.accounts.reward_vault, &ctx.accounts.reward_mint, &ctx.accounts.yield_recipient_account, &ctx.accounts.reward_token_program) {
+                        (Some(reward_vault), Some(reward_mint), Some(yield_recipient_account), Some(reward_token_program)) => {
+                            execute_token_transfer(
+                                stake_info.yield_pending,
+                                reward_mint.decimals,
+                                reward_vault.to_account_info(),
+                                reward_mint.to_account_info(),
+                                yield_recipient_account.to_account_info(),
+                                ctx.accounts.campaign_auth.to_account_info(),
+                                reward_token_program.to_account_info(),
+                                Some(&[&[campaign.as_ref(), &[ctx.bumps.campaign_auth]]]),
+                                ctx.remaining_accounts)?;
+                            stake_info.yield_pending = 0;
+                        },
+                        (_, _, _, _) => return err!(ErrorCodes::StakeYieldUnclaimed),
+                    }
+                }
+            }
 
             stake_info.amount = 0;
             stake_info.campaign_end_time = 0;
@@ -34,10 +81,17 @@ pub fn claim_stake(ctx: Context<ClaimStake>) -> Result<()> {
 
 #[derive(Accounts)]
 pub struct ClaimStake<'info>{
-    #[account(mut, realloc=8+CampaignPlayer::INIT_SPACE-StakeInfo::INIT_SPACE, realloc::payer = user, realloc::zero=true)]
+    #[account(mut,
+        seeds=[b"player", campaign_player.campaign.as_ref(), user.key().as_ref()],
+        bump,
+        realloc=8+CampaignPlayer::INIT_SPACE-StakeInfo::INIT_SPACE, realloc::payer = user, realloc::zero=true,
+        constraint = campaign_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
     pub campaign_player: Box<Account<'info, CampaignPlayer>>,
     #[account(mut)]
     pub user: Signer<'info>,
+    /// CHECK: the player's campaign, its stake totals are updated while it exists
+    #[account(mut, address = campaign_player.campaign @ ErrorCodes::InvalidInput)]
+    pub campaign: AccountInfo<'info>,
     ///CHECK: auth
     #[account(seeds=[campaign_player.campaign.as_ref()], bump)]
     pub campaign_auth: AccountInfo<'info>,
@@ -47,16 +101,39 @@ pub struct ClaimStake<'info>{
         seeds=[b"player_deposit", campaign_player.campaign.as_ref()], 
         bump
     )]
-    pub game_deposit_vault: InterfaceAccount<'info, TokenAccount>,
+    pub game_deposit_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(
         init_if_needed, 
         payer=user, 
         associated_token::authority=user, 
         associated_token::mint = game_deposit_mint, 
         associated_token::token_program = token_program)]
-    pub stake_recipient_account: Box<InterfaceAccount<'info, TokenAccount>>,
-    pub game_deposit_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub stake_recipient_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(constraint = campaign_player.stake_info.as_ref().is_some_and(|s| s.staked_mint == game_deposit_mint.key()) @ ErrorCodes::NoStake)]
+    pub game_deposit_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
-}
\ No newline at end of file
+
+    /// lamport deposits of campaigns priced in SOL, replaces the token accounts above
+    #[account(mut, seeds=[b"sol_deposit", campaign_player.campaign.as_ref()], bump)]
+    pub sol_deposit_vault: Option<SystemAccount<'info>>,
+
+    /// the campaign reward vault, only needed to pay out settled stake yield
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        seeds=[b"rewards", campaign_player.campaign.as_ref()],
+        bump
+    )]
+    pub reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub reward_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    #[account(
+        init_if_needed,
+        payer=user,
+        associated_token::authority=user,
+        associated_token::mint = reward_mint,
+        associated_token::token_program = reward_token_program)]
+    pub yield_recipient_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub reward_token_program: Option<Interface<'info, TokenInterface>>,
+}
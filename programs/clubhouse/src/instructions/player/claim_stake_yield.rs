@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface}};
+
+use crate::{errors::ErrorCodes, execute_token_transfer, state::CampaignPlayer, Campaign, ACCOUNT_VERSION};
+
+pub fn claim_stake_yield<'info>(ctx: Context<'_, '_, '_, 'info, ClaimStakeYield<'info>>) -> Result<()> {
+    let now_ts = Clock::get()?.unix_timestamp;
+    let campaign = &mut ctx.accounts.campaign;
+    let stake_info = ctx.accounts.campaign_player.stake_info.as_mut().ok_or(ErrorCodes::NoStake)?;
+
+    campaign.accrue_stake_yield(now_ts);
+    stake_info.settle_yield(campaign.yield_per_stake);
+    let amount = stake_info.yield_pending;
+    require!(amount > 0, ErrorCodes::InsufficientFunds);
+    stake_info.yield_pending = 0;
+
+    let campaign_key = campaign.key();
+    execute_token_transfer(
+        amount,
+        campaign.reward_mint_decimals,
+        ctx.accounts.reward_vault.to_account_info(),
+        ctx.accounts.reward_mint.to_account_info(),
+        ctx.accounts.yield_recipient_account.to_account_info(),
+        ctx.accounts.campaign_auth.to_account_info(),
+        ctx.accounts.reward_token_program.to_account_info(),
+        Some(&[&[campaign_key.as_ref(), &[campaign.auth_bump]]]),
+        ctx.remaining_accounts)?;
+    msg!("stake yield claimed: {}", amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimStakeYield<'info> {
+    #[account(mut, has_one=reward_mint, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+    #[account(mut, has_one=campaign,
+        seeds=[b"player", campaign.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = campaign_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign_player: Box<Account<'info, CampaignPlayer>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    ///CHECK: auth
+    #[account(seeds=[campaign.key().as_ref()], bump)]
+    pub campaign_auth: AccountInfo<'info>,
+
+    pub reward_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        seeds=[b"rewards", campaign.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer=user,
+        associated_token::authority=user,
+        associated_token::mint = reward_mint,
+        associated_token::token_program = reward_token_program)]
+    pub yield_recipient_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,3 +1,13 @@
 pub mod claim_stake;
+pub mod transfer_energy;
+pub mod claim_energy;
+pub mod unstake_early;
+pub mod stake_tokens;
+pub mod claim_stake_yield;
 
-pub use claim_stake::*;
\ No newline at end of file
+pub use claim_stake::*;
+pub use transfer_energy::*;
+pub use claim_energy::*;
+pub use unstake_early::*;
+pub use stake_tokens::*;
+pub use claim_stake_yield::*;
\ No newline at end of file
@@ -0,0 +1,78 @@
+use std::ops::AddAssign;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{errors::ErrorCodes, execute_token_transfer, get_amount_after_transfer_fee, state::CampaignPlayer, Campaign, House, IdentityType, PlayerIdentity, TokenUse, ACCOUNT_VERSION};
+
+pub fn stake_tokens<'info>(ctx: Context<'_, '_, '_, 'info, StakeTokens<'info>>, amount: u64) -> Result<()> {
+    let token_config = ctx.accounts.campaign.token_config.ok_or(ErrorCodes::InvalidInput)?;
+    require!(token_config.token_use == TokenUse::StakeForEnergy, ErrorCodes::InvalidInput);
+    require!(amount > 0, ErrorCodes::InvalidInput);
+    let now_ts = Clock::get()?.unix_timestamp;
+    require!(!ctx.accounts.campaign.time_span.is_expired(now_ts), ErrorCodes::CampaignExpired);
+
+    execute_token_transfer(
+        amount,
+        token_config.spending_mint_decimals,
+        ctx.accounts.players_deposit_account.to_account_info(),
+        ctx.accounts.game_deposit_mint.to_account_info(),
+        ctx.accounts.game_deposit_vault.to_account_info(),
+        ctx.accounts.user.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        None,
+        ctx.remaining_accounts)?;
+    // only what reached the vault can be returned
+    let amount_received = get_amount_after_transfer_fee(&ctx.accounts.game_deposit_mint.to_account_info(), amount)?;
+
+    let campaign = &mut ctx.accounts.campaign;
+    let campaign_player = &mut ctx.accounts.campaign_player;
+    let identity = PlayerIdentity { identity_type: IdentityType::User, pubkey: ctx.accounts.user.key() };
+    if campaign_player.player_identity.identity_type == IdentityType::None {
+        //new or reinitialized campaign player
+        campaign_player.set_inner(CampaignPlayer::new(identity, &campaign, 0)?);
+        ctx.accounts.house.unique_players.add_assign(1);
+        campaign.player_count.add_assign(1);
+    }
+    require!(campaign_player.player_identity == identity, ErrorCodes::PlayerIdentityMismatch);
+
+    // settle energy earned under the current tier before the stake changes it
+    let _ = campaign_player.recharge_stake_energy(campaign, now_ts)?;
+    let stake_info = campaign_player.stake_info.as_mut().ok_or(ErrorCodes::NoStake)?;
+    campaign.add_stake(stake_info, amount_received, now_ts)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct StakeTokens<'info> {
+    #[account(mut, address = campaign.house @ ErrorCodes::InvalidInput, constraint = house.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub house: Box<Account<'info, House>>,
+    #[account(mut, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+    #[account(init_if_needed, space=8+CampaignPlayer::INIT_SPACE,
+        seeds=[b"player", campaign.key().as_ref(), user.key().as_ref()],
+        bump,
+        payer = user,
+        constraint = campaign_player.player_identity.identity_type == IdentityType::None || campaign_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign_player: Box<Account<'info, CampaignPlayer>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(constraint = campaign.token_config.is_some_and(|c| c.spending_mint == game_deposit_mint.key()) @ ErrorCodes::InvalidInput)]
+    pub game_deposit_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        token::mint = game_deposit_mint,
+        token::authority = user,
+    )]
+    pub players_deposit_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds=[b"player_deposit", campaign.key().as_ref()],
+        bump
+    )]
+    pub game_deposit_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
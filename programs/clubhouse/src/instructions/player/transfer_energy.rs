@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{errors::ErrorCodes, state::{CampaignPlayer, CoreAsset}, Campaign, IdentityType, ACCOUNT_VERSION};
+
+pub fn transfer_energy(ctx: Context<TransferEnergy>, amount: u8) -> Result<()> {
+    let campaign = &ctx.accounts.campaign;
+    let nft_config = campaign.nft_config.ok_or(ErrorCodes::InvalidInput)?;
+    require!(nft_config.max_energy_transfer_per_day > 0, ErrorCodes::EnergyTransferDisabled);
+    require!(!nft_config.use_house_energy, ErrorCodes::EnergyTransferDisabled);
+    require!(amount > 0, ErrorCodes::InvalidInput);
+    ctx.accounts.validate_source_owner()?;
+
+    let now_ts = Clock::get()?.unix_timestamp;
+    let source_player = &mut ctx.accounts.source_player;
+    let destination_player = &mut ctx.accounts.destination_player;
+
+    // each side recharges and caps with its own collection and trait settings
+    let source_config = source_player.nft_config(campaign)?;
+    let destination_config = destination_player.nft_config(campaign)?;
+    let _ = source_player.recharge_energy(&source_config, now_ts)?;
+    let _ = destination_player.recharge_energy(&destination_config, now_ts)?;
+
+    source_player.send_energy(amount, &source_config.ok_or(ErrorCodes::InvalidInput)?, now_ts)?;
+    destination_player.receive_energy(amount, &destination_config.ok_or(ErrorCodes::InvalidInput)?, now_ts)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TransferEnergy<'info> {
+    #[account(constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+
+    #[account(mut, has_one=campaign,
+        seeds=[b"player", campaign.key().as_ref(), source_player.player_identity.pubkey.as_ref()],
+        bump,
+        constraint = source_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub source_player: Box<Account<'info, CampaignPlayer>>,
+
+    #[account(mut, has_one=campaign,
+        seeds=[b"player", campaign.key().as_ref(), destination_player.player_identity.pubkey.as_ref()],
+        bump,
+        constraint = destination_player.key() != source_player.key() @ ErrorCodes::InvalidInput,
+        constraint = destination_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub destination_player: Box<Account<'info, CampaignPlayer>>,
+
+    #[account(
+        constraint = player_nft_token_account.mint == source_player.player_identity.pubkey @ ErrorCodes::PlayerIdentityMismatch,
+        constraint = player_nft_token_account.owner == user.key() @ ErrorCodes::TokenOwnerMismatch,
+        constraint = player_nft_token_account.amount == 1 @ ErrorCodes::OwnerBalanceMismatch,
+    )]
+    pub player_nft_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: Custom validation for mpl-core asset
+    #[account()]
+    pub player_core_nft: Option<AccountInfo<'info>>,
+
+    pub user: Signer<'info>,
+}
+
+impl TransferEnergy<'_> {
+    /// Only the current holder of the source NFT can give its energy away
+    pub fn validate_source_owner(&self) -> Result<()> {
+        require!(
+            matches!(self.destination_player.player_identity.identity_type, IdentityType::Nft | IdentityType::MplCore | IdentityType::Token2022Nft),
+            ErrorCodes::PlayerIdentityMismatch
+        );
+        match (self.source_player.player_identity.identity_type, &self.player_nft_token_account, &self.player_core_nft) {
+            (IdentityType::Nft, Some(_), None) | (IdentityType::Token2022Nft, Some(_), None) => Ok(()),
+            (IdentityType::MplCore, None, Some(core_nft_info)) => {
+                require!(core_nft_info.key() == self.source_player.player_identity.pubkey, ErrorCodes::PlayerIdentityMismatch);
+                let nft = CoreAsset::from_account_info(core_nft_info)?;
+                require!(nft.owner == self.user.key(), ErrorCodes::TokenOwnerMismatch);
+                Ok(())
+            },
+            (_, _, _) => err!(ErrorCodes::InvalidInput),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token_interface::{Mint, TokenAccount, TokenInterface}};
+
+use crate::{errors::ErrorCodes, execute_token_burn, execute_token_transfer, get_amount_after_transfer_fee, state::CampaignPlayer, Campaign, PenaltyDestination, TokenUse, ACCOUNT_VERSION};
+
+pub fn unstake_early<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeEarly<'info>>, amount: u64) -> Result<()> {
+    let token_config = ctx.accounts.campaign.token_config.ok_or(ErrorCodes::NoStake)?;
+    let early_unstake = token_config.early_unstake.ok_or(ErrorCodes::EarlyUnstakeDisabled)?;
+    require!(!ctx.accounts.campaign_player.in_game, ErrorCodes::PlayerInGame);
+
+    let now_ts = Clock::get()?.unix_timestamp;
+    let stake_energy = token_config.token_use == TokenUse::StakeForEnergy;
+
+    let campaign_player = &mut ctx.accounts.campaign_player;
+    if stake_energy {
+        // settle energy earned under the current tier before the stake shrinks
+        let _ = campaign_player.recharge_stake_energy(&ctx.accounts.campaign, now_ts)?;
+    }
+    let stake_info = campaign_player.stake_info.as_mut().ok_or(ErrorCodes::NoStake)?;
+    // once the campaign is over the full stake is returned by claim_stake
+    require!(stake_info.campaign_end_time >= now_ts, ErrorCodes::CampaignExpired);
+    require!(amount > 0, ErrorCodes::InvalidInput);
+    require!(amount <= stake_info.amount, ErrorCodes::AmountTooHigh);
+
+    let penalty = early_unstake.penalty(amount);
+    let campaign_key = ctx.accounts.campaign.key();
+    let seeds: &[&[&[u8]]] = &[&[campaign_key.as_ref(), &[ctx.bumps.campaign_auth]]];
+
+    execute_token_transfer(
+        amount - penalty,
+        stake_info.staked_mint_decimals,
+        ctx.accounts.game_deposit_vault.to_account_info(),
+        ctx.accounts.game_deposit_mint.to_account_info(),
+        ctx.accounts.stake_recipient_account.to_account_info(),
+        ctx.accounts.campaign_auth.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        Some(seeds),
+        ctx.remaining_accounts)?;
+
+    if penalty > 0 {
+        match (early_unstake.penalty_destination, &ctx.accounts.reward_vault, &ctx.accounts.creator_token_account) {
+            (PenaltyDestination::Burn, None, None) => {
+                execute_token_burn(
+                    penalty,
+                    ctx.accounts.game_deposit_mint.to_account_info(),
+                    ctx.accounts.game_deposit_vault.to_account_info(),
+                    ctx.accounts.campaign_auth.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    Some(seeds))?;
+            },
+            (PenaltyDestination::RewardPool, Some(reward_vault), None) => {
+                execute_token_transfer(
+                    penalty,
+                    stake_info.staked_mint_decimals,
+                    ctx.accounts.game_deposit_vault.to_account_info(),
+                    ctx.accounts.game_deposit_mint.to_account_info(),
+                    reward_vault.to_account_info(),
+                    ctx.accounts.campaign_auth.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    Some(seeds),
+                    ctx.remaining_accounts)?;
+                let amount_received = get_amount_after_transfer_fee(&ctx.accounts.game_deposit_mint.to_account_info(), penalty)?;
+                ctx.accounts.campaign.rewards_available = ctx.accounts.campaign.rewards_available.checked_add(amount_received).ok_or(ErrorCodes::InvalidInput)?;
+            },
+            (PenaltyDestination::Creator, None, Some(creator_token_account)) => {
+                execute_token_transfer(
+                    penalty,
+                    stake_info.staked_mint_decimals,
+                    ctx.accounts.game_deposit_vault.to_account_info(),
+                    ctx.accounts.game_deposit_mint.to_account_info(),
+                    creator_token_account.to_account_info(),
+                    ctx.accounts.campaign_auth.to_account_info(),
+                    ctx.accounts.token_program.to_account_info(),
+                    Some(seeds),
+                    ctx.remaining_accounts)?;
+            },
+            (_, _, _) => return err!(ErrorCodes::InvalidInput),
+        }
+    }
+
+    // the player account keeps its stake slot so later games can stake into it again
+    ctx.accounts.campaign.remove_stake(stake_info, amount, now_ts)?;
+    if stake_energy {
+        let energy = ctx.accounts.campaign_player.recharge_stake_energy(&ctx.accounts.campaign, now_ts)?;
+        msg!("stake tier energy after unstaking: {}", energy);
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UnstakeEarly<'info> {
+    #[account(mut, constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Box<Account<'info, Campaign>>,
+    #[account(mut, has_one=campaign,
+        seeds=[b"player", campaign.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = campaign_player.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign_player: Box<Account<'info, CampaignPlayer>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    ///CHECK: auth
+    #[account(seeds=[campaign.key().as_ref()], bump)]
+    pub campaign_auth: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds=[b"player_deposit", campaign.key().as_ref()],
+        bump
+    )]
+    pub game_deposit_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer=user,
+        associated_token::authority=user,
+        associated_token::mint = game_deposit_mint,
+        associated_token::token_program = token_program)]
+    pub stake_recipient_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, constraint = campaign_player.stake_info.as_ref().is_some_and(|s| s.staked_mint == game_deposit_mint.key()) @ ErrorCodes::NoStake)]
+    pub game_deposit_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// the campaign reward vault, only when the penalty goes to the reward pool
+    #[account(
+        mut,
+        token::mint = game_deposit_mint,
+        seeds=[b"rewards", campaign.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// the creator's account, only when the penalty goes to the creator
+    #[account(
+        mut,
+        token::mint = game_deposit_mint,
+        token::authority = campaign.creator,
+    )]
+    pub creator_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,89 @@
+use anchor_lang::{prelude::*, Discriminator};
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{errors::ErrorCodes, execute_lamport_transfer, string_len_borsh, string_option_len, vec_len_borsh, Campaign, CampaignPlayer, CampaignPlayerV0, CampaignV0, HouseConfig, House, HouseV0, PlayerIdentity, StakeInfo, TimeSpan, TokenUse, ACCOUNT_VERSION};
+
+
+/// Rewrites a House, Campaign or CampaignPlayer created before ACCOUNT_VERSION in the current layout.
+/// Houses go first, then their campaigns, then the campaign players. Anyone can migrate, the payer funds the extra space
+pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+    let account = ctx.accounts.legacy_account.to_account_info();
+    let (migrated, space) = {
+        let data = account.try_borrow_data()?;
+        require!(data.len() > 8, ErrorCodes::InvalidInput);
+        let (discriminator, mut legacy) = data.split_at(8);
+        let version_offset = if discriminator == House::DISCRIMINATOR {
+            // the manager_collection option is the only variable field ahead of the version
+            1 + 32 + if legacy.get(33) == Some(&1) { 33 } else { 1 } + 32 + 1 + 4 + 2 + 4 + 4 + 8 + 8 + 1
+        } else if discriminator == Campaign::DISCRIMINATOR {
+            1 + 32 + 32 + PlayerIdentity::INIT_SPACE + 32 + 1 + 8 + 8 + 4 + 4 + 4 + TimeSpan::INIT_SPACE + HouseConfig::INIT_SPACE
+        } else if discriminator == CampaignPlayer::DISCRIMINATOR {
+            PlayerIdentity::INIT_SPACE + 32 + 32 + 1 + 8 + 8 + 4 + 1 + 8
+        } else {
+            return err!(ErrorCodes::InvalidInput);
+        };
+        require!(legacy.get(version_offset).is_some_and(|v| *v < ACCOUNT_VERSION), ErrorCodes::AccountAlreadyMigrated);
+
+        let mut migrated = vec![];
+        let space = if discriminator == House::DISCRIMINATOR {
+            House::from_legacy(HouseV0::deserialize(&mut legacy)?).try_serialize(&mut migrated)?;
+            // the reserved bytes absorbed the version
+            data.len()
+        } else if discriminator == Campaign::DISCRIMINATOR {
+            let legacy = CampaignV0::deserialize(&mut legacy)?;
+            let total_staked = match (legacy.token_config.map(|c| c.token_use), &ctx.accounts.game_deposit_vault) {
+                (Some(TokenUse::Stake), Some(game_deposit_vault)) => game_deposit_vault.amount,
+                (Some(TokenUse::Stake), None) => return err!(ErrorCodes::InvalidInput),
+                (_, _) => 0,
+            };
+            let campaign = Campaign::from_legacy(legacy, total_staked);
+            campaign.try_serialize(&mut migrated)?;
+            8 + Campaign::FIXED_SPACE
+                + 4 * vec_len_borsh(0, 0)
+                + string_len_borsh(&campaign.campaign_name)
+                + string_option_len(&campaign.uri)
+        } else {
+            let legacy = CampaignPlayerV0::deserialize(&mut legacy)?;
+            let campaign = ctx.accounts.campaign.as_ref().ok_or(ErrorCodes::InvalidInput)?;
+            require_keys_eq!(legacy.campaign, campaign.key(), ErrorCodes::InvalidInput);
+            let campaign_player = CampaignPlayer::from_legacy(legacy, campaign, Clock::get()?.unix_timestamp);
+            campaign_player.try_serialize(&mut migrated)?;
+            8 + CampaignPlayer::INIT_SPACE - campaign_player.stake_info.as_ref().map_or(StakeInfo::INIT_SPACE, |_| 0)
+        };
+        (migrated, space.max(data.len()))
+    };
+
+    if space > account.data_len() {
+        let rent = Rent::get()?.minimum_balance(space).saturating_sub(account.lamports());
+        if rent > 0 {
+            execute_lamport_transfer(
+                rent,
+                ctx.accounts.payer.to_account_info(),
+                account.clone(),
+                ctx.accounts.system_program.to_account_info(),
+                None)?;
+        }
+        account.realloc(space, false)?;
+    }
+    let mut data = account.try_borrow_mut_data()?;
+    data[..migrated.len()].copy_from_slice(&migrated);
+    data[migrated.len()..].fill(0);
+    msg!("account migrated to version {}", ACCOUNT_VERSION);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: a House, Campaign or CampaignPlayer told apart by discriminator, the version byte rejects current layouts
+    #[account(mut, owner = crate::id() @ ErrorCodes::InvalidInput)]
+    pub legacy_account: UncheckedAccount<'info>,
+    /// the already migrated campaign of a campaign player
+    #[account(constraint = campaign.version == ACCOUNT_VERSION @ ErrorCodes::AccountNotMigrated)]
+    pub campaign: Option<Box<Account<'info, Campaign>>>,
+    /// stake deposits of a legacy stake campaign, sets total_staked
+    #[account(seeds=[b"player_deposit", legacy_account.key().as_ref()], bump)]
+    pub game_deposit_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,5 +1,7 @@
 pub mod add;
 pub mod remove;
+pub mod migrate;
 
 pub use add::*;
-pub use remove::*;
\ No newline at end of file
+pub use remove::*;
+pub use migrate::*;
\ No newline at end of file
@@ -17,19 +17,19 @@ pub mod clubhouse {
 
     use super::*;
 
-    pub fn create_house(ctx: Context<CreateHouse>, manager_collection: Option<Pubkey>, house_config: HouseConfig, house_name: String, uri: Option<String>) -> Result<()> {
-        house::create::create_house(ctx, manager_collection, house_config, house_name, uri)
+    pub fn create_house(ctx: Context<CreateHouse>, manager_collection: Option<Pubkey>, house_config: HouseConfig, house_name: String, uri: Option<String>, player_energy: Option<EnergyConfig>, manager_collection_verification: CollectionVerification) -> Result<()> {
+        house::create::create_house(ctx, manager_collection, house_config, house_name, uri, player_energy, manager_collection_verification)
     }
 
-    pub fn update_house(ctx: Context<UpdateHouse>, house_config: HouseConfig, uri: Option<String>) -> Result<()> {
-        house::update::update_house(ctx, house_config, uri)
+    pub fn update_house(ctx: Context<UpdateHouse>, house_config: HouseConfig, uri: Option<String>, player_energy: Option<EnergyConfig>) -> Result<()> {
+        house::update::update_house(ctx, house_config, uri, player_energy)
     }
 
-    pub fn withdraw_house_fees(ctx: Context<WithdrawHouseFees>) -> Result<()> {
+    pub fn withdraw_house_fees<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawHouseFees<'info>>) -> Result<()> {
         house::withdraw::withdraw_house_fees(ctx)
     }
 
-    pub fn close_house(ctx: Context<CloseHouse>) -> Result<()> {
+    pub fn close_house<'info>(ctx: Context<'_, '_, '_, 'info, CloseHouse<'info>>) -> Result<()> {
         house::close::close_house(ctx)
     }
 
@@ -37,26 +37,74 @@ pub mod clubhouse {
         program_admin::add::add_program_admin(ctx)
     }
 
-    pub fn create_campaign(ctx: Context<CreateCampaign>, campaign_name: String, custom_data: Option<String>, fund_amount: u64, max_rewards_per_game: u64, player_claim_price: u64, time_span: TimeSpan, nft_config: Option<NftCampaignConfig>, token_config: Option<TokenCampaignConfig>, burn_remainder: bool) -> Result<()> {
-        campaign::create_campaign(ctx, campaign_name, custom_data, fund_amount, max_rewards_per_game, player_claim_price, time_span, nft_config, token_config, burn_remainder)
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        program_admin::migrate::migrate_account(ctx)
     }
 
-    pub fn close_campaign(ctx: Context<CloseCampaign>) -> Result<()> {
+    pub fn create_campaign<'info>(ctx: Context<'_, '_, '_, 'info, CreateCampaign<'info>>, campaign_name: String, custom_data: Option<String>, fund_amount: u64, max_rewards_per_game: u64, player_claim_price: u64, time_span: TimeSpan, nft_config: Option<NftCampaignConfig>, token_config: Option<TokenCampaignConfig>, burn_remainder: bool, game_modes: Vec<GameMode>, collections: Vec<CollectionEntry>, trait_multipliers: Vec<TraitMultiplier>, stake_tiers: Vec<StakeTier>) -> Result<()> {
+        campaign::create_campaign(ctx, campaign_name, custom_data, fund_amount, max_rewards_per_game, player_claim_price, time_span, nft_config, token_config, burn_remainder, game_modes, collections, trait_multipliers, stake_tiers)
+    }
+
+    pub fn close_campaign<'info>(ctx: Context<'_, '_, '_, 'info, CloseCampaign<'info>>) -> Result<()> {
         campaign::close_campaign(ctx)
     }
 
-    pub fn start_game(ctx: Context<StartGame>) -> Result<()> {
-        game::start_game(ctx)
+    pub fn slash_stake(ctx: Context<SlashStake>, amount: u64, reason_code: u16, destination: PenaltyDestination) -> Result<()> {
+        campaign::slash_stake(ctx, amount, reason_code, destination)
+    }
+
+    pub fn restore_stake<'info>(ctx: Context<'_, '_, '_, 'info, RestoreStake<'info>>) -> Result<()> {
+        campaign::restore_stake(ctx)
+    }
+
+    pub fn finalize_slash<'info>(ctx: Context<'_, '_, '_, 'info, FinalizeSlash<'info>>) -> Result<()> {
+        campaign::finalize_slash(ctx)
+    }
+
+    pub fn withdraw_revenue<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawRevenue<'info>>) -> Result<()> {
+        campaign::withdraw_revenue(ctx)
+    }
+
+    pub fn fund_campaign<'info>(ctx: Context<'_, '_, '_, 'info, FundCampaign<'info>>, amount: u64) -> Result<()> {
+        campaign::fund_campaign(ctx, amount)
+    }
+
+    pub fn start_game<'info>(ctx: Context<'_, '_, '_, 'info, StartGame<'info>>, game_mode: u8, compressed_nft: Option<CompressedNftProof>, trait_proof: Option<TraitProof>, max_price: u64) -> Result<()> {
+        game::start_game(ctx, game_mode, compressed_nft, trait_proof, max_price)
     }
 
-    pub fn end_game(ctx: Context<EndGame>, amount_won: u64) -> Result<()> {
-        game::end_game(ctx, amount_won)
+    pub fn end_game<'info>(ctx: Context<'_, '_, '_, 'info, EndGame<'info>>, amount_won: u64, compressed_nft: Option<CompressedNftProof>) -> Result<()> {
+        game::end_game(ctx, amount_won, compressed_nft)
     }
 
-    pub fn claim_stake(ctx: Context<ClaimStake>) -> Result<()> {
+    pub fn expire_game(ctx: Context<ExpireGame>) -> Result<()> {
+        game::expire_game(ctx)
+    }
+
+    pub fn claim_stake<'info>(ctx: Context<'_, '_, '_, 'info, ClaimStake<'info>>) -> Result<()> {
         player::claim_stake(ctx)
     }
 
+    pub fn transfer_energy(ctx: Context<TransferEnergy>, amount: u8) -> Result<()> {
+        player::transfer_energy(ctx, amount)
+    }
+
+    pub fn claim_energy<'info>(ctx: Context<'_, '_, '_, 'info, ClaimEnergy<'info>>, compressed_nft: Option<CompressedNftProof>) -> Result<()> {
+        player::claim_energy(ctx, compressed_nft)
+    }
+
+    pub fn unstake_early<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeEarly<'info>>, amount: u64) -> Result<()> {
+        player::unstake_early(ctx, amount)
+    }
+
+    pub fn stake_tokens<'info>(ctx: Context<'_, '_, '_, 'info, StakeTokens<'info>>, amount: u64) -> Result<()> {
+        player::stake_tokens(ctx, amount)
+    }
+
+    pub fn claim_stake_yield<'info>(ctx: Context<'_, '_, '_, 'info, ClaimStakeYield<'info>>) -> Result<()> {
+        player::claim_stake_yield(ctx)
+    }
+
 
 
 
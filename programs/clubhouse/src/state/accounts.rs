@@ -1,6 +1,14 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
 
-use crate::errors::ErrorCodes;
+use crate::{errors::ErrorCodes, Attribute, CampaignPlayerV0, CampaignV0, HouseV0};
+
+/// `version` of House, Campaign and CampaignPlayer. Accounts written before versioning hold 0 or 1
+/// at that offset and have to go through migrate_account, see the legacy module
+pub const ACCOUNT_VERSION: u8 = 2;
+
+/// denominator of every basis point multiplier and penalty
+pub const BPS_DENOMINATOR: u16 = 10_000;
 
 #[account]
 #[derive(InitSpace)]
@@ -17,6 +25,20 @@ pub struct ManagerSlot {
     pub house: Pubkey,
 }
 
+/// A stake seized by the oracle or house admin, held in the deposit vault until the dispute window passes
+#[account]
+#[derive(InitSpace, Debug)]
+pub struct StakeSlash {
+    pub campaign: Pubkey,
+    pub campaign_player: Pubkey,
+    /// submitted the slash and receives the rent back
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub reason_code: u16,
+    pub destination: PenaltyDestination,
+    pub dispute_ends_at: i64,
+}
+
 #[account]
 pub struct Campaign {
     pub auth_bump: u8, 
@@ -32,19 +54,403 @@ pub struct Campaign {
     pub total_games: u32,
     pub time_span: TimeSpan,
     pub house_config_snapshot: HouseConfig,
+    pub version: u8,
     pub nft_config: Option<NftCampaignConfig>,
     pub unclaimed_sol_fees: u64,
-    pub _reserved_config: [u64; 7],
+    /// stake yield allocation not yet emitted to stakers
+    pub stake_yield_remaining: u64,
+    /// stake yield emitted per staked token, scaled by YIELD_PRECISION
+    pub yield_per_stake: u128,
+    pub yield_updated_at: i64,
+    /// slashed stake awaiting finalize_slash or restore_stake
+    pub pending_slashes: u64,
+    /// creator share of Pay deposits held in the deposit vault, see withdraw_revenue
+    pub creator_revenue: u64,
+    pub _reserved_config: [u64; 1],
     pub token_config: Option<TokenCampaignConfig>,
-    pub _reserved_for_token: [u64; 2],
-    pub _reserved_bytes: [u8; 7],
+    /// stake deposits not yet returned by claim_stake
+    pub total_staked: u64,
+    /// players with an outstanding stake
+    pub staker_count: u32,
+    pub _reserved_for_token: [u8; 4],
+    /// bump of the sol_deposit PDA holding lamport deposits of native campaigns
+    pub sol_deposit_bump: u8,
+    /// bump of the sol_rewards PDA paying the rewards of native reward campaigns
+    pub sol_rewards_bump: u8,
+    pub _reserved_bytes: [u8; 5],
     pub burn_remainder: bool,
     pub rewards_available: u64,
     pub reserved_rewards: u64,
+    pub game_modes: Vec<GameMode>,
+    /// additional collections with their own energy settings, nft campaigns only
+    pub collections: Vec<CollectionEntry>,
+    /// bonuses for player NFTs carrying specific traits, nft campaigns only
+    pub trait_multipliers: Vec<TraitMultiplier>,
+    /// energy granted by stake size and age, ascending by min_amount, stake for energy campaigns only
+    pub stake_tiers: Vec<StakeTier>,
     pub campaign_name: String,
     pub uri: Option<String>,
 }
 
+impl Campaign {
+    pub const MAX_GAME_MODES: usize = 8;
+    pub const MAX_COLLECTIONS: usize = 8;
+    pub const MAX_TRAIT_MULTIPLIERS: usize = 16;
+    pub const MAX_STAKE_TIERS: usize = 8;
+    pub const YIELD_PRECISION: u128 = 1_000_000_000_000;
+    /// space of every field but the vecs and strings
+    pub const FIXED_SPACE: usize = 1 + 32 + 32 + PlayerIdentity::INIT_SPACE + 32 + 1 + 8 + 8 + 4 + 4 + 4
+        + TimeSpan::INIT_SPACE + HouseConfig::INIT_SPACE + 1 + 1 + NftCampaignConfig::INIT_SPACE
+        + 8 + 8 + 16 + 8 + 8 + 8 + 8 + 1 + TokenCampaignConfig::INIT_SPACE
+        + 8 + 4 + 4 + 1 + 1 + 5 + 1 + 8 + 8;
+
+    pub fn from_legacy(legacy: CampaignV0, total_staked: u64) -> Campaign {
+        Campaign {
+            auth_bump: legacy.auth_bump,
+            house: legacy.house,
+            creator: legacy.creator,
+            manager_identity: legacy.manager_identity,
+            reward_mint: legacy.reward_mint,
+            reward_mint_decimals: legacy.reward_mint_decimals,
+            max_rewards_per_game: legacy.max_rewards_per_game,
+            rewards_claim_fee: legacy.rewards_claim_fee,
+            player_count: legacy.player_count,
+            active_games: legacy.active_games,
+            total_games: legacy.total_games,
+            time_span: legacy.time_span,
+            house_config_snapshot: legacy.house_config_snapshot,
+            version: ACCOUNT_VERSION,
+            nft_config: legacy.nft_config.map(|c| NftCampaignConfig {
+                collection: c.collection,
+                max_player_energy: c.max_player_energy,
+                energy_recharge_minutes: c.energy_recharge_minutes,
+                energy_reset: None,
+                use_house_energy: false,
+                max_energy_transfer_per_day: 0,
+                tokenized_energy: false,
+                lock_player_nft: false,
+                max_game_seconds: None,
+                allow_compressed_nfts: true,
+                allow_token2022_nfts: true,
+                collection_verification: CollectionVerification::Either,
+                trait_root: None,
+            }),
+            unclaimed_sol_fees: legacy.unclaimed_sol_fees,
+            stake_yield_remaining: 0,
+            yield_per_stake: 0,
+            yield_updated_at: 0,
+            pending_slashes: 0,
+            creator_revenue: 0,
+            _reserved_config: [0; 1],
+            token_config: legacy.token_config.map(|c| TokenCampaignConfig {
+                spending_mint: c.spending_mint,
+                energy_price: c.energy_price,
+                spending_mint_decimals: c.spending_mint_decimals,
+                token_use: c.token_use,
+                early_unstake: None,
+                stake_yield: None,
+                slash_dispute_seconds: None,
+                revenue_split: None,
+                price_curve: None,
+            }),
+            total_staked,
+            // only reported by close_campaign, remove_stake saturates
+            staker_count: 0,
+            _reserved_for_token: [0; 4],
+            sol_deposit_bump: 0,
+            sol_rewards_bump: 0,
+            _reserved_bytes: [0; 5],
+            burn_remainder: legacy.burn_remainder,
+            rewards_available: legacy.rewards_available,
+            reserved_rewards: legacy.reserved_rewards,
+            game_modes: vec![],
+            collections: vec![],
+            trait_multipliers: vec![],
+            stake_tiers: vec![],
+            campaign_name: legacy.campaign_name,
+            uri: legacy.uri,
+        }
+    }
+
+    /// Campaigns created without explicit modes expose a single mode built from the legacy fields
+    pub fn get_game_mode(&self, index: u8) -> Result<GameMode> {
+        if self.game_modes.is_empty() {
+            require!(index == 0, ErrorCodes::InvalidGameMode);
+            return Ok(GameMode {
+                energy_cost: 1,
+                energy_price: self.token_config.map_or(0, |c| c.energy_price),
+                max_rewards_per_game: self.max_rewards_per_game,
+                oracle: None,
+            });
+        }
+        self.game_modes.get(index as usize).copied().ok_or(error!(ErrorCodes::InvalidGameMode))
+    }
+
+    pub fn get_oracle(&self, game_mode: &GameMode) -> Pubkey {
+        game_mode.oracle.unwrap_or(self.house_config_snapshot.oracle_key)
+    }
+
+    /// Rewards are lamports held by the sol_rewards PDA instead of tokens in the rewards vault
+    pub fn has_native_rewards(&self) -> bool {
+        self.reward_mint == System::id()
+    }
+
+    /// Price of a game of `mode` for the player, following the campaign price curve
+    pub fn game_price(&self, mode: &GameMode, player: &CampaignPlayer, now_ts: i64) -> u64 {
+        match self.token_config.and_then(|c| c.price_curve) {
+            Some(price_curve) => price_curve.price(mode.energy_price, self, player.games_on_day(now_ts)),
+            None => mode.energy_price,
+        }
+    }
+
+    pub fn is_oracle(&self, key: &Pubkey) -> bool {
+        self.house_config_snapshot.oracle_key == *key || self.game_modes.iter().any(|mode| mode.oracle == Some(*key))
+    }
+
+    /// Campaigns created without explicit collections accept the nft_config collection alone
+    pub fn get_collection(&self, index: u8) -> Result<CollectionEntry> {
+        let nft_config = self.nft_config.ok_or(ErrorCodes::InvalidInput)?;
+        if self.collections.is_empty() {
+            require!(index == 0, ErrorCodes::CollectionKeyMismatch);
+            return Ok(CollectionEntry {
+                collection: nft_config.collection,
+                max_player_energy: nft_config.max_player_energy,
+                energy_recharge_minutes: nft_config.energy_recharge_minutes,
+                reward_multiplier_bps: BPS_DENOMINATOR,
+            });
+        }
+        self.collections.get(index as usize).copied().ok_or(error!(ErrorCodes::CollectionKeyMismatch))
+    }
+
+    /// Index of the first campaign collection accepted by `is_member`
+    pub fn find_collection(&self, is_member: impl Fn(&Pubkey) -> bool) -> Result<u8> {
+        let count = self.collections.len().max(1) as u8;
+        for index in 0..count {
+            if is_member(&self.get_collection(index)?.collection) {
+                return Ok(index);
+            }
+        }
+        err!(ErrorCodes::CollectionKeyMismatch)
+    }
+
+    /// The nft config with the energy settings of the player's collection applied
+    pub fn player_nft_config(&self, collection_index: u8) -> Result<Option<NftCampaignConfig>> {
+        let Some(nft_config) = self.nft_config else {
+            return Ok(None);
+        };
+        let entry = self.get_collection(collection_index)?;
+        Ok(Some(NftCampaignConfig {
+            collection: entry.collection,
+            max_player_energy: entry.max_player_energy,
+            energy_recharge_minutes: entry.energy_recharge_minutes,
+            ..nft_config
+        }))
+    }
+
+    /// Emits stake yield up to now, capped at the campaign end, shared by the stake held meanwhile
+    pub fn accrue_stake_yield(&mut self, now_ts: i64) {
+        let Some(stake_yield) = self.token_config.and_then(|c| c.stake_yield) else {
+            return;
+        };
+        let until = now_ts.min(self.time_span.end_time);
+        if until <= self.yield_updated_at {
+            return;
+        }
+        if self.total_staked > 0 {
+            let elapsed = (until - self.yield_updated_at) as u64;
+            let emitted = elapsed.saturating_mul(stake_yield.rewards_per_second).min(self.stake_yield_remaining);
+            self.yield_per_stake += emitted as u128 * Self::YIELD_PRECISION / self.total_staked as u128;
+            self.stake_yield_remaining -= emitted;
+        }
+        self.yield_updated_at = until;
+    }
+
+    /// Adds a deposit to a player stake, settling the yield earned by the previous amount
+    pub fn add_stake(&mut self, stake_info: &mut StakeInfo, amount: u64, now_ts: i64) -> Result<()> {
+        self.accrue_stake_yield(now_ts);
+        stake_info.settle_yield(self.yield_per_stake);
+        if stake_info.amount == 0 && amount > 0 {
+            self.staker_count += 1;
+        }
+        stake_info.deposit(amount, now_ts)?;
+        self.total_staked = self.total_staked.checked_add(amount).ok_or(ErrorCodes::AmountTooHigh)?;
+        stake_info.sync_yield_debt(self.yield_per_stake);
+        Ok(())
+    }
+
+    /// Takes a withdrawal out of a player stake, settling the yield earned by the previous amount
+    pub fn remove_stake(&mut self, stake_info: &mut StakeInfo, amount: u64, now_ts: i64) -> Result<()> {
+        self.accrue_stake_yield(now_ts);
+        stake_info.settle_yield(self.yield_per_stake);
+        stake_info.amount = stake_info.amount.checked_sub(amount).ok_or(ErrorCodes::AmountTooHigh)?;
+        self.total_staked = self.total_staked.saturating_sub(amount);
+        if stake_info.amount == 0 && amount > 0 {
+            self.staker_count = self.staker_count.saturating_sub(1);
+        }
+        stake_info.sync_yield_debt(self.yield_per_stake);
+        Ok(())
+    }
+
+    /// The highest tier the stake qualifies for by amount and time staked
+    pub fn stake_tier(&self, stake_info: &StakeInfo, now_ts: i64) -> Option<&StakeTier> {
+        let staked_minutes = now_ts.saturating_sub(stake_info.staked_since) / CampaignPlayer::SEC_PER_MINUTE;
+        self.stake_tiers
+            .iter()
+            .rev()
+            .find(|tier| stake_info.amount >= tier.min_amount && staked_minutes >= tier.min_staked_minutes)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct StakeTier {
+    pub min_amount: u64,
+    /// how long the stake must have been held, top-ups move the start by their weight
+    pub min_staked_minutes: i64,
+    pub energy: EnergyConfig,
+}
+
+impl StakeTier {
+    pub fn validate_tiers(tiers: &[StakeTier]) -> Result<()> {
+        require!(tiers.len() <= Campaign::MAX_STAKE_TIERS, ErrorCodes::InvalidInput);
+        require!(tiers.windows(2).all(|pair| pair[0].min_amount <= pair[1].min_amount), ErrorCodes::InvalidInput);
+        for tier in tiers {
+            require!(tier.min_staked_minutes >= 0, ErrorCodes::InvalidInput);
+            require!(tier.energy.max_player_energy > 0, ErrorCodes::InvalidEnergyConfig);
+            tier.energy.validate()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct CollectionEntry {
+    pub collection: Pubkey,
+    pub max_player_energy: u8,
+    pub energy_recharge_minutes: Option<i64>,
+    /// scales the max rewards of every game played with this collection
+    pub reward_multiplier_bps: u16,
+}
+
+impl CollectionEntry {
+    pub fn apply_multiplier(&self, amount: u64) -> u64 {
+        (amount as u128 * self.reward_multiplier_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum MultiplierTarget {
+    Rewards,
+    MaxEnergy,
+    RechargeSpeed,
+}
+
+/// Scales `target` for player NFTs whose `trait_type` attribute equals `value`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct TraitMultiplier {
+    #[max_len(32)]
+    pub trait_type: String,
+    #[max_len(32)]
+    pub value: String,
+    pub target: MultiplierTarget,
+    pub multiplier_bps: u16,
+}
+
+impl TraitMultiplier {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.trait_type.len() <= 32 && self.value.len() <= 32, ErrorCodes::StringTooLong);
+        require!(self.multiplier_bps > 0, ErrorCodes::InvalidInput);
+        Ok(())
+    }
+}
+
+/// Traits of a Token Metadata NFT proven against the campaign `trait_root`.
+/// Leaves are keccak(nft || borsh(traits)) and pairs are hashed in sorted order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct TraitProof {
+    pub traits: Vec<Attribute>,
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl TraitProof {
+    pub fn verify(&self, root: &[u8; 32], nft: &Pubkey) -> Result<()> {
+        let traits = self.traits.try_to_vec().map_err(|_| ErrorCodes::TraitProofInvalid)?;
+        let mut node = keccak::hashv(&[nft.as_ref(), &traits]).to_bytes();
+        for sibling in &self.proof {
+            node = match node <= *sibling {
+                true => keccak::hashv(&[&node, sibling]).to_bytes(),
+                false => keccak::hashv(&[sibling, &node]).to_bytes(),
+            };
+        }
+        require!(node == *root, ErrorCodes::TraitProofInvalid);
+        Ok(())
+    }
+}
+
+/// Multipliers earned from the traits of the player NFT, refreshed at every start_game
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct TraitBonus {
+    pub rewards_bps: u16,
+    pub max_energy_bps: u16,
+    pub recharge_bps: u16,
+}
+
+impl Default for TraitBonus {
+    fn default() -> Self {
+        TraitBonus {
+            rewards_bps: BPS_DENOMINATOR,
+            max_energy_bps: BPS_DENOMINATOR,
+            recharge_bps: BPS_DENOMINATOR,
+        }
+    }
+}
+
+impl TraitBonus {
+    /// Matching multipliers compound per target
+    pub fn from_traits(multipliers: &[TraitMultiplier], traits: &[Attribute]) -> TraitBonus {
+        let mut bonus = TraitBonus::default();
+        for multiplier in multipliers {
+            if !traits.iter().any(|t| t.key == multiplier.trait_type && t.value == multiplier.value) {
+                continue;
+            }
+            let bps = match multiplier.target {
+                MultiplierTarget::Rewards => &mut bonus.rewards_bps,
+                MultiplierTarget::MaxEnergy => &mut bonus.max_energy_bps,
+                MultiplierTarget::RechargeSpeed => &mut bonus.recharge_bps,
+            };
+            *bps = Self::scale(*bps as u64, multiplier.multiplier_bps).min(u16::MAX as u64) as u16;
+        }
+        bonus
+    }
+
+    fn scale(amount: u64, bps: u16) -> u64 {
+        (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    pub fn apply_rewards(&self, amount: u64) -> u64 {
+        Self::scale(amount, self.rewards_bps)
+    }
+
+    /// Raises the energy cap and shortens the recharge interval
+    pub fn apply(&self, config: NftCampaignConfig) -> NftCampaignConfig {
+        NftCampaignConfig {
+            max_player_energy: Self::scale(config.max_player_energy.into(), self.max_energy_bps).min(u8::MAX.into()) as u8,
+            energy_recharge_minutes: config.energy_recharge_minutes
+                .map(|minutes| (minutes * BPS_DENOMINATOR as i64 / self.recharge_bps.max(1) as i64).max(1)),
+            ..config
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct GameMode {
+    pub energy_cost: u8,
+    /// price per game in the spending mint, token campaigns only
+    pub energy_price: u64,
+    pub max_rewards_per_game: u64,
+    /// overrides the house oracle for this mode
+    pub oracle: Option<Pubkey>,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct House {
@@ -70,8 +476,13 @@ pub struct House {
     pub unclaimed_house_fees: u64,
     /// house fees pending withdrawal
     pub is_active: bool,
+    pub version: u8,
+    /// shared energy pool for campaigns opting into house energy
+    pub player_energy: Option<EnergyConfig>,
+    /// how `manager_collection` must appear on manager pass metadata
+    pub manager_collection_verification: CollectionVerification,
 
-    _reserved1: [u64; 16],
+    _reserved1: [u8; 98],
 
     pub config: HouseConfig,
 
@@ -86,26 +497,59 @@ pub struct House {
 }
 
 impl House {
+    pub fn from_legacy(legacy: HouseV0) -> House {
+        House {
+            bump: legacy.bump,
+            house_admin: legacy.house_admin,
+            manager_collection: legacy.manager_collection,
+            house_currency: legacy.house_currency,
+            house_currency_decimals: legacy.house_currency_decimals,
+            total_campaigns: legacy.total_campaigns,
+            open_campaigns: legacy.open_campaigns,
+            unique_players: legacy.unique_players,
+            games_played: legacy.games_played,
+            unclaimed_sol_fees: legacy.unclaimed_sol_fees,
+            unclaimed_house_fees: legacy.unclaimed_house_fees,
+            is_active: legacy.is_active,
+            version: ACCOUNT_VERSION,
+            player_energy: None,
+            manager_collection_verification: CollectionVerification::Either,
+            _reserved1: [0; 98],
+            config: legacy.config,
+            _reserved2: [0; 16],
+            house_name: legacy.house_name,
+            uri: legacy.uri,
+        }
+    }
+
     pub fn initialize(
         &mut self,
         house_admin: Pubkey,
         manager_collection: Option<Pubkey>,
+        manager_collection_verification: CollectionVerification,
         house_currency: Pubkey,
         house_currency_decimals: u8,
         config: HouseConfig,
+        player_energy: Option<EnergyConfig>,
         house_name: String,
         uri: Option<String>,
         bump: u8,
     ) -> Result<()>{
         crate::common::validate_string(&house_name)?;
+        if let Some(energy_config) = player_energy {
+            energy_config.validate()?;
+        }
         self.house_admin = house_admin;
         self.manager_collection = manager_collection;
+        self.manager_collection_verification = manager_collection_verification;
         self.house_currency = house_currency;
         self.house_currency_decimals = house_currency_decimals;
         self.config = config;
+        self.player_energy = player_energy;
         self.house_name = house_name;
         self.bump = bump;
         self.is_active = true;
+        self.version = ACCOUNT_VERSION;
         self.uri = uri;
         Ok(())
     }
@@ -140,14 +584,338 @@ pub struct NftCampaignConfig {
     pub collection: Pubkey,
     pub max_player_energy: u8,
     pub energy_recharge_minutes: Option<i64>,
+    /// refills energy at fixed period boundaries, exclusive with energy_recharge_minutes
+    pub energy_reset: Option<EnergyReset>,
+    /// draw energy from the house-wide HousePlayer pool instead of the CampaignPlayer
+    pub use_house_energy: bool,
+    /// energy a player may gift per UTC day, 0 disables transfers
+    pub max_energy_transfer_per_day: u8,
+    /// energy is claimed as tokens of the campaign energy mint and burned to play
+    pub tokenized_energy: bool,
+    /// freeze the player NFT under campaign_auth from start_game until end_game
+    pub lock_player_nft: bool,
+    /// seconds after which anyone may expire_game and release a locked NFT, required with lock_player_nft
+    pub max_game_seconds: Option<i64>,
+    /// compressed NFTs may play, they cannot be locked
+    pub allow_compressed_nfts: bool,
+    /// Token-2022 group member NFTs may play, they cannot be locked
+    pub allow_token2022_nfts: bool,
+    /// how `collection` must appear on the player NFT metadata
+    pub collection_verification: CollectionVerification,
+    /// creator-published merkle root of Token Metadata NFT traits, see TraitProof
+    pub trait_root: Option<[u8; 32]>,
+}
+
+impl NftCampaignConfig {
+    pub fn energy_config(&self) -> EnergyConfig {
+        EnergyConfig {
+            max_player_energy: self.max_player_energy,
+            energy_recharge_minutes: self.energy_recharge_minutes,
+            energy_reset: self.energy_reset,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require!(!(self.tokenized_energy && self.use_house_energy), ErrorCodes::InvalidEnergyConfig);
+        if self.lock_player_nft {
+            require!(!self.allow_compressed_nfts && !self.allow_token2022_nfts, ErrorCodes::NftLockUnsupported);
+            require!(self.max_game_seconds.is_some_and(|s| s > 0), ErrorCodes::InvalidInput);
+        }
+        self.energy_config().validate()
+    }
+
+    /// Whether players may join with this kind of identity
+    pub fn allows_identity(&self, identity_type: IdentityType) -> bool {
+        match identity_type {
+            IdentityType::CompressedNft => self.allow_compressed_nfts,
+            IdentityType::Token2022Nft => self.allow_token2022_nfts,
+            _ => true,
+        }
+    }
+}
+
+/// Which verified metadata entries may prove collection membership. A creator address that minted
+/// several collections unlocks all of them unless the collection itself is required
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace, Default)]
+pub enum CollectionVerification {
+    #[default]
+    Either,
+    Collection,
+    Creator,
+}
+
+impl CollectionVerification {
+    pub fn allows_collection(&self) -> bool {
+        matches!(self, CollectionVerification::Either | CollectionVerification::Collection)
+    }
+
+    pub fn allows_creator(&self) -> bool {
+        matches!(self, CollectionVerification::Either | CollectionVerification::Creator)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct EnergyConfig {
+    pub max_player_energy: u8,
+    pub energy_recharge_minutes: Option<i64>,
+    /// refills energy at fixed period boundaries, exclusive with energy_recharge_minutes
+    pub energy_reset: Option<EnergyReset>,
+}
+
+impl EnergyConfig {
+    pub fn validate(&self) -> Result<()> {
+        match (self.energy_recharge_minutes, self.energy_reset) {
+            (Some(_), Some(_)) => err!(ErrorCodes::InvalidEnergyConfig),
+            (Some(recharge_minutes), None) if recharge_minutes <= 0 => err!(ErrorCodes::InvalidEnergyConfig),
+            (None, Some(reset)) if reset.period_minutes <= 0 => err!(ErrorCodes::InvalidEnergyConfig),
+            _ => Ok(()),
+        }
+    }
+
+    /// Brings the energy fields of a player account up to date and returns the resulting energy
+    pub fn recharge(
+        &self,
+        energy: &mut u8,
+        recharge_start_time: &mut i64,
+        last_reset_period: &mut i64,
+        now_ts: i64,
+    ) -> u8 {
+        match (self.energy_recharge_minutes, self.energy_reset) {
+            (Some(recharge_minutes), _) => {
+                if *energy >= self.max_player_energy {
+                    return *energy;
+                }
+                let recharge_seconds = recharge_minutes
+                    .checked_mul(CampaignPlayer::SEC_PER_MINUTE)
+                    .unwrap();
+                let time_passed_since_update =
+                    now_ts.checked_sub(*recharge_start_time).unwrap();
+                // whole energy points replenished since last update
+                let energy_restored_since_update = time_passed_since_update
+                    .checked_div(recharge_seconds)
+                    .unwrap();
+                // if energy is not full, the last update happened at the last recharge, otherwise it happened now
+                let last_recharge_tick_if_not_full = recharge_start_time
+                    .checked_add(
+                        energy_restored_since_update
+                            .checked_mul(recharge_seconds)
+                            .unwrap(),
+                    )
+                    .unwrap();
+                let estimated_energy = i64::from(*energy)
+                    .checked_add(energy_restored_since_update)
+                    .unwrap();
+
+                let energy_is_maxed = estimated_energy >= self.max_player_energy.into();
+                (*energy, *recharge_start_time) = match energy_is_maxed {
+                    true => (self.max_player_energy, now_ts),
+                    false => (
+                        estimated_energy.try_into().unwrap(),
+                        last_recharge_tick_if_not_full,
+                    ),
+                };
+                msg!("energy recharged, remaining: {}", *energy);
+                *energy
+            },
+            (None, Some(reset)) => {
+                let current_period = reset.period_at(now_ts);
+                if current_period > *last_reset_period {
+                    *energy = (*energy).max(self.max_player_energy);
+                    *last_reset_period = current_period;
+                    *recharge_start_time = now_ts;
+                    msg!("energy reset, remaining: {}", *energy);
+                }
+                *energy
+            },
+            (None, None) => *energy,
+        }
+    }
+
+    pub fn initial_reset_period(&self, now_ts: i64) -> i64 {
+        self.energy_reset.map_or(0, |r| r.period_at(now_ts))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct EnergyReset {
+    /// 1440 resets once a day
+    pub period_minutes: i64,
+    /// timestamp periods are aligned to, 0 aligns daily resets to 00:00 UTC
+    pub epoch: i64,
+}
+
+impl EnergyReset {
+    pub fn period_at(&self, now_ts: i64) -> i64 {
+        let period_seconds = self.period_minutes
+            .checked_mul(CampaignPlayer::SEC_PER_MINUTE)
+            .unwrap();
+        now_ts.checked_sub(self.epoch).unwrap().div_euclid(period_seconds)
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
 pub struct TokenCampaignConfig {
+    /// the system program id prices games in lamports, see TokenCampaignConfig::is_native
     pub spending_mint: Pubkey,
     pub energy_price: u64,
     pub spending_mint_decimals: u8,
     pub token_use: TokenUse,
+    /// lets stakers withdraw before the campaign ends for a penalty, stake campaigns only
+    pub early_unstake: Option<EarlyUnstakeConfig>,
+    /// reward pool share paid to stakers over time, stake campaigns only
+    pub stake_yield: Option<StakeYieldConfig>,
+    /// seconds the house admin has to overturn a slash, None disables slashing, stake campaigns only
+    pub slash_dispute_seconds: Option<i64>,
+    /// splits each Pay deposit at start_game, the unsplit remainder is burned
+    pub revenue_split: Option<RevenueSplit>,
+    /// raises the game mode energy_price, None charges it flat
+    pub price_curve: Option<PriceCurve>,
+}
+
+impl TokenCampaignConfig {
+    pub const NATIVE_DECIMALS: u8 = 9;
+
+    /// Deposits are lamports held by the sol_deposit PDA instead of tokens in the player_deposit vault
+    pub fn is_native(&self) -> bool {
+        self.spending_mint == System::id()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.is_native() {
+            // lamports can neither be burned nor moved into the reward token vault
+            require!(matches!(self.token_use, TokenUse::Pay | TokenUse::Stake), ErrorCodes::InvalidInput);
+            require!(self.spending_mint_decimals == Self::NATIVE_DECIMALS, ErrorCodes::InvalidInput);
+            require!(
+                self.early_unstake.is_none() && self.slash_dispute_seconds.is_none() && self.revenue_split.is_none(),
+                ErrorCodes::InvalidInput
+            );
+        }
+        if let Some(early_unstake) = self.early_unstake {
+            require!(self.token_use.is_stake(), ErrorCodes::InvalidInput);
+            require!(early_unstake.penalty_bps <= BPS_DENOMINATOR, ErrorCodes::InvalidInput);
+        }
+        if let Some(stake_yield) = self.stake_yield {
+            require!(self.token_use.is_stake(), ErrorCodes::InvalidInput);
+            require!(stake_yield.rewards_per_second > 0, ErrorCodes::InvalidInput);
+        }
+        if let Some(dispute_seconds) = self.slash_dispute_seconds {
+            require!(self.token_use.is_stake(), ErrorCodes::InvalidInput);
+            require!(dispute_seconds >= 0, ErrorCodes::InvalidInput);
+        }
+        if self.revenue_split.is_some() {
+            require!(self.token_use == TokenUse::Pay, ErrorCodes::InvalidInput);
+        }
+        if let Some(price_curve) = self.price_curve {
+            require!(self.token_use != TokenUse::StakeForEnergy, ErrorCodes::InvalidInput);
+            price_curve.validate()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum PriceCurve {
+    /// adds step_bps of the base price for every `games` games the campaign has finished
+    TotalGames { games: u32, step_bps: u16 },
+    /// adds up to max_increase_bps as rewards_available drains from full_pool to zero
+    RewardsRemaining { full_pool: u64, max_increase_bps: u16 },
+    /// adds step_bps for every game the player already started this UTC day
+    DailyEscalating { step_bps: u16 },
+}
+
+impl PriceCurve {
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            PriceCurve::TotalGames { games, .. } => require!(games > 0, ErrorCodes::InvalidInput),
+            PriceCurve::RewardsRemaining { full_pool, .. } => require!(full_pool > 0, ErrorCodes::InvalidInput),
+            PriceCurve::DailyEscalating { .. } => {},
+        }
+        Ok(())
+    }
+
+    pub fn price(&self, base_price: u64, campaign: &Campaign, games_today: u16) -> u64 {
+        let increase_bps = match *self {
+            PriceCurve::TotalGames { games, step_bps } => (campaign.total_games / games.max(1)) as u128 * step_bps as u128,
+            PriceCurve::RewardsRemaining { full_pool, max_increase_bps } => {
+                let drained = full_pool.saturating_sub(campaign.rewards_available) as u128;
+                drained * max_increase_bps as u128 / full_pool.max(1) as u128
+            },
+            PriceCurve::DailyEscalating { step_bps } => games_today as u128 * step_bps as u128,
+        };
+        let price = base_price as u128 * (BPS_DENOMINATOR as u128 + increase_bps) / BPS_DENOMINATOR as u128;
+        price.min(u64::MAX as u128) as u64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct RevenueSplit {
+    pub creator_bps: u16,
+    /// None applies the house rewards_tax as basis points
+    pub house_bps: Option<u16>,
+    pub reward_pool_bps: u16,
+}
+
+/// One Pay deposit divided by a RevenueSplit
+pub struct RevenueShares {
+    pub creator: u64,
+    pub house: u64,
+    pub reward_pool: u64,
+    pub burn: u64,
+}
+
+impl RevenueSplit {
+    pub fn house_bps(&self, house_config: &HouseConfig) -> u16 {
+        self.house_bps.unwrap_or(house_config.rewards_tax.min(BPS_DENOMINATOR as u64) as u16)
+    }
+
+    pub fn validate(&self, house_config: &HouseConfig) -> Result<()> {
+        let total = self.creator_bps as u32 + self.house_bps(house_config) as u32 + self.reward_pool_bps as u32;
+        require!(total <= BPS_DENOMINATOR as u32, ErrorCodes::TaxTooHigh);
+        Ok(())
+    }
+
+    /// Rounding leftovers go to the burned share
+    pub fn shares(&self, amount: u64, house_config: &HouseConfig) -> RevenueShares {
+        let share = |bps: u16| (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64;
+        let creator = share(self.creator_bps);
+        let house = share(self.house_bps(house_config));
+        let reward_pool = share(self.reward_pool_bps);
+        RevenueShares {
+            creator,
+            house,
+            reward_pool,
+            burn: amount.saturating_sub(creator + house + reward_pool),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct StakeYieldConfig {
+    /// reward tokens moved out of rewards_available at creation to fund the yield
+    pub allocation: u64,
+    /// emitted across all stakers in proportion to their stake
+    pub rewards_per_second: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct EarlyUnstakeConfig {
+    pub penalty_bps: u16,
+    pub penalty_destination: PenaltyDestination,
+}
+
+impl EarlyUnstakeConfig {
+    pub fn penalty(&self, amount: u64) -> u64 {
+        // rounded up so splitting an unstake into small amounts does not dodge the penalty
+        (amount as u128 * self.penalty_bps as u128).div_ceil(BPS_DENOMINATOR as u128) as u64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum PenaltyDestination {
+    Burn,
+    /// added to rewards_available, requires the reward mint to be the staked mint
+    RewardPool,
+    Creator,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
@@ -155,6 +923,15 @@ pub enum TokenUse {
     Stake,
     Burn,
     Pay,
+    /// deposited once with stake_tokens, the stake tier sets energy instead of paying per game
+    StakeForEnergy,
+}
+
+impl TokenUse {
+    /// Deposits are escrowed in the player_deposit vault and returned by claim_stake
+    pub fn is_stake(&self) -> bool {
+        matches!(self, TokenUse::Stake | TokenUse::StakeForEnergy)
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
@@ -204,6 +981,24 @@ pub struct CampaignPlayer {
     pub games_played: u32,
     pub in_game: bool,
     pub rewards_claimed: u64,
+    pub version: u8,
+    /// mode of the current or last game
+    pub game_mode: u8,
+    /// last energy reset period the player was refilled in
+    pub last_reset_period: i64,
+    /// UTC day of the last outgoing energy transfer
+    pub energy_transfer_day: i64,
+    /// energy sent during energy_transfer_day
+    pub energy_transferred: u8,
+    /// campaign collection the player NFT belongs to
+    pub collection_index: u8,
+    /// max rewards of the current game, after the collection multiplier
+    pub game_max_rewards: u64,
+    pub trait_bonus: TraitBonus,
+    /// UTC day of games_today
+    pub price_day: i64,
+    /// games started during price_day, used by the daily escalating price curve
+    pub games_today: u16,
     pub stake_info: Option<StakeInfo>,
 }
 
@@ -211,6 +1006,84 @@ impl CampaignPlayer{
     pub fn get_identity(&self) -> PlayerIdentity {
         self.player_identity
     }
+
+    /// Legacy stakes count as deposited at migration, legacy campaigns have no stake tiers or yield
+    pub fn from_legacy(legacy: CampaignPlayerV0, campaign: &Campaign, now_ts: i64) -> CampaignPlayer {
+        CampaignPlayer {
+            player_identity: legacy.player_identity,
+            campaign: legacy.campaign,
+            house: legacy.house,
+            energy: legacy.energy,
+            recharge_start_time: legacy.recharge_start_time,
+            game_start_time: legacy.game_start_time,
+            games_played: legacy.games_played,
+            in_game: legacy.in_game,
+            rewards_claimed: legacy.rewards_claimed,
+            version: ACCOUNT_VERSION,
+            game_mode: 0,
+            last_reset_period: 0,
+            energy_transfer_day: 0,
+            energy_transferred: 0,
+            collection_index: 0,
+            // legacy games reserved the campaign max rewards
+            game_max_rewards: match legacy.in_game {
+                true => campaign.max_rewards_per_game,
+                false => 0,
+            },
+            trait_bonus: TraitBonus::default(),
+            price_day: 0,
+            games_today: 0,
+            stake_info: legacy.stake_info.map(|stake_info| StakeInfo {
+                amount: stake_info.amount,
+                campaign_end_time: stake_info.campaign_end_time,
+                staked_mint: stake_info.staked_mint,
+                staked_mint_decimals: stake_info.staked_mint_decimals,
+                campaign_name: stake_info.campaign_name,
+                staked_since: now_ts,
+                yield_debt: 0,
+                yield_pending: 0,
+            }),
+        }
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+/// Energy shared by every campaign of a house that opts into house energy
+pub struct HousePlayer {
+    pub player_identity: PlayerIdentity,
+    pub house: Pubkey,
+    pub energy: u8,
+    pub recharge_start_time: i64,
+    pub last_reset_period: i64,
+}
+
+impl HousePlayer {
+    pub fn new(identity: PlayerIdentity, house: Pubkey, energy_config: &EnergyConfig) -> Result<HousePlayer> {
+        let clock = Clock::get()?;
+        Ok(HousePlayer {
+            player_identity: identity,
+            house,
+            energy: energy_config.max_player_energy,
+            recharge_start_time: clock.unix_timestamp,
+            last_reset_period: energy_config.initial_reset_period(clock.unix_timestamp),
+        })
+    }
+
+    pub fn recharge_energy(&mut self, energy_config: &EnergyConfig, now_ts: i64) -> u8 {
+        energy_config.recharge(
+            &mut self.energy,
+            &mut self.recharge_start_time,
+            &mut self.last_reset_period,
+            now_ts,
+        )
+    }
+
+    pub fn spend_energy(&mut self, energy_to_spend: u8) -> Result<()> {
+        self.energy = self.energy.checked_sub(energy_to_spend).ok_or(ErrorCodes::OutOfEnergy)?;
+        msg!("house energy spent, remaining: {}", self.energy);
+        Ok(())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
@@ -221,6 +1094,36 @@ pub struct StakeInfo{
     pub staked_mint_decimals: u8,
     #[max_len(32)]
     pub campaign_name: String,
+    /// amount weighted start of the stake, used by stake tiers
+    pub staked_since: i64,
+    /// campaign yield_per_stake already accounted for at the current amount
+    pub yield_debt: u128,
+    /// settled stake yield not yet paid out
+    pub yield_pending: u64,
+}
+
+impl StakeInfo {
+    /// Adds a deposit, moving staked_since forward by the share of new tokens
+    pub fn deposit(&mut self, amount: u64, now_ts: i64) -> Result<()> {
+        let total = self.amount.checked_add(amount).ok_or(ErrorCodes::AmountTooHigh)?;
+        if total > 0 {
+            let weighted = self.staked_since as i128 * self.amount as i128 + now_ts as i128 * amount as i128;
+            self.staked_since = (weighted / total as i128) as i64;
+        }
+        self.amount = total;
+        Ok(())
+    }
+
+    /// Moves the yield earned since the last settlement into yield_pending
+    pub fn settle_yield(&mut self, yield_per_stake: u128) {
+        let earned = (self.amount as u128 * yield_per_stake / Campaign::YIELD_PRECISION).saturating_sub(self.yield_debt);
+        self.yield_pending = self.yield_pending.saturating_add(earned as u64);
+        self.yield_debt += earned;
+    }
+
+    pub fn sync_yield_debt(&mut self, yield_per_stake: u128) {
+        self.yield_debt = self.amount as u128 * yield_per_stake / Campaign::YIELD_PRECISION;
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
@@ -229,6 +1132,8 @@ pub enum IdentityType{
     Nft,
     User,
     MplCore,
+    CompressedNft,
+    Token2022Nft,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
@@ -247,6 +1152,8 @@ impl PlayerIdentity {
             IdentityType::Nft => Some(self.pubkey),
             IdentityType::User => Some(self.pubkey),
             IdentityType::MplCore => Some(self.pubkey),
+            IdentityType::CompressedNft => Some(self.pubkey),
+            IdentityType::Token2022Nft => Some(self.pubkey),
         }
     }
     
@@ -254,30 +1161,47 @@ impl PlayerIdentity {
 
 impl CampaignPlayer {
     pub const SEC_PER_MINUTE: i64 = 60;
+    pub const SEC_PER_DAY: i64 = 86400;
 
     pub fn new<'info>(
         identity: PlayerIdentity,
         campaign: &Account<'info, Campaign>,
+        collection_index: u8,
     ) -> Result<CampaignPlayer> {
         let clock = Clock::get()?;
+        let nft_config = campaign.player_nft_config(collection_index)?;
         Ok(CampaignPlayer {
             player_identity: identity,
             campaign: campaign.key(),
             house: campaign.house,
-            energy: campaign.nft_config.map_or(0, |c| c.max_player_energy),
+            energy: nft_config.map_or(0, |c| c.max_player_energy),
             recharge_start_time: clock.unix_timestamp,
             games_played: 0,
             in_game: false,
             game_start_time: 0,
             rewards_claimed: 0,
+            version: ACCOUNT_VERSION,
+            game_mode: 0,
+            last_reset_period: nft_config
+                .map_or(0, |c| c.energy_config().initial_reset_period(clock.unix_timestamp)),
+            energy_transfer_day: 0,
+            energy_transferred: 0,
+            collection_index,
+            game_max_rewards: 0,
+            trait_bonus: TraitBonus::default(),
+            price_day: 0,
+            games_today: 0,
             stake_info: {
-                if campaign.token_config.is_some_and(|c| c.token_use == TokenUse::Stake) {
+                if campaign.token_config.is_some_and(|c| c.token_use.is_stake()) {
                     Some(StakeInfo {
                         amount: 0,
                         campaign_end_time: campaign.time_span.end_time,
                         staked_mint: campaign.token_config.unwrap().spending_mint,
                         staked_mint_decimals: campaign.token_config.unwrap().spending_mint_decimals,
                         campaign_name: campaign.campaign_name.clone(),
+                        staked_since: clock.unix_timestamp,
+                        yield_debt: 0,
+                        yield_pending: 0,
                     })
                 } else {
                     None
@@ -287,6 +1211,11 @@ impl CampaignPlayer {
         })
     }
     
+    /// The nft config of the player's collection with the trait bonus applied
+    pub fn nft_config(&self, campaign: &Campaign) -> Result<Option<NftCampaignConfig>> {
+        Ok(campaign.player_nft_config(self.collection_index)?.map(|config| self.trait_bonus.apply(config)))
+    }
+
     pub fn recharge_energy(
         &mut self,
         energy_config: &Option<NftCampaignConfig>,
@@ -294,59 +1223,106 @@ impl CampaignPlayer {
     ) -> Result<u8> {
         match energy_config {
             None => return Ok(self.energy),
-            Some(config) => {
-                match config.energy_recharge_minutes {
-                    None => return Ok(self.energy),
-                    Some(recharge_minutes) => {
-                        if self.energy >= config.max_player_energy {
-                            return Ok(self.energy);
-                        }
-                        let recharge_seconds = (recharge_minutes as i64)
-                            .checked_mul(CampaignPlayer::SEC_PER_MINUTE)
-                            .unwrap();
-                        let time_passed_since_update =
-                            now_ts.checked_sub(self.recharge_start_time).unwrap();
-                        // whole energy points replenished since last update
-                        let energy_restored_since_update = time_passed_since_update
-                            .checked_div(recharge_seconds)
-                            .unwrap();
-                        // if energy is not full, the last update happened at the last recharge, otherwise it happened now
-                        let last_recharge_tick_if_not_full = self
-                            .recharge_start_time
-                            .checked_add(
-                                energy_restored_since_update
-                                    .checked_mul(recharge_seconds)
-                                    .unwrap(),
-                            )
-                            .unwrap();
-                        let estimated_energy = i64::from(self.energy)
-                            .checked_add(energy_restored_since_update)
-                            .unwrap();
-                        
-                        let energy_is_maxed = estimated_energy >= config.max_player_energy.into();
-                        (self.energy, self.recharge_start_time) = match energy_is_maxed {
-                            true => (config.max_player_energy, now_ts),
-                            false => (
-                                estimated_energy.try_into().unwrap(),
-                                last_recharge_tick_if_not_full,
-                            ),
-                        };
-                        msg!("energy recharged, remaining: {}", self.energy);
-                        Ok(self.energy)
-                    },
-                }
+            Some(config) => Ok(config.energy_config().recharge(
+                &mut self.energy,
+                &mut self.recharge_start_time,
+                &mut self.last_reset_period,
+                now_ts,
+            )),
+        }
+    }
+
+    /// Brings stake tier energy up to date, capping it when the stake dropped to a lower tier
+    pub fn recharge_stake_energy(&mut self, campaign: &Campaign, now_ts: i64) -> Result<u8> {
+        let stake_info = self.stake_info.as_ref().ok_or(ErrorCodes::NoStake)?;
+        match campaign.stake_tier(stake_info, now_ts) {
+            Some(tier) => {
+                tier.energy.recharge(
+                    &mut self.energy,
+                    &mut self.recharge_start_time,
+                    &mut self.last_reset_period,
+                    now_ts,
+                );
+                self.energy = self.energy.min(tier.energy.max_player_energy);
+            },
+            None => {
+                self.energy = 0;
+                self.recharge_start_time = now_ts;
             },
         }
-        
-        
+        Ok(self.energy)
+    }
+
+    pub fn games_on_day(&self, now_ts: i64) -> u16 {
+        match self.price_day == now_ts.div_euclid(CampaignPlayer::SEC_PER_DAY) {
+            true => self.games_today,
+            false => 0,
+        }
+    }
+
+    pub fn record_game_day(&mut self, now_ts: i64) {
+        self.games_today = self.games_on_day(now_ts).saturating_add(1);
+        self.price_day = now_ts.div_euclid(CampaignPlayer::SEC_PER_DAY);
+    }
+
+    /// Removes gifted energy, restarting the recharge clock if the pool was full and therefore paused
+    pub fn send_energy(&mut self, amount: u8, config: &NftCampaignConfig, now_ts: i64) -> Result<()> {
+        let today = now_ts.div_euclid(CampaignPlayer::SEC_PER_DAY);
+        if self.energy_transfer_day != today {
+            self.energy_transfer_day = today;
+            self.energy_transferred = 0;
+        }
+        self.energy_transferred = self.energy_transferred
+            .checked_add(amount)
+            .filter(|sent| *sent <= config.max_energy_transfer_per_day)
+            .ok_or(ErrorCodes::EnergyTransferLimitExceeded)?;
+
+        if self.energy >= config.max_player_energy {
+            self.recharge_start_time = now_ts;
+        }
+        self.energy = self.energy.checked_sub(amount).ok_or(ErrorCodes::OutOfEnergy)?;
+        msg!("energy sent, remaining: {}", self.energy);
+        Ok(())
+    }
+
+    /// Adds gifted energy up to the cap, pausing the recharge clock once the pool is full
+    pub fn receive_energy(&mut self, amount: u8, config: &NftCampaignConfig, now_ts: i64) -> Result<()> {
+        self.energy = self.energy
+            .checked_add(amount)
+            .filter(|energy| *energy <= config.max_player_energy)
+            .ok_or(ErrorCodes::AmountTooHigh)?;
+        if self.energy >= config.max_player_energy {
+            self.recharge_start_time = now_ts;
+        }
+        msg!("energy received, remaining: {}", self.energy);
+        Ok(())
+    }
+
+    /// Empties the energy pool for minting as tokens, returns the amount taken
+    pub fn withdraw_energy(&mut self, config: &NftCampaignConfig, now_ts: i64) -> Result<u8> {
+        let energy = self.recharge_energy(&Some(*config), now_ts)?;
+        require!(energy > 0, ErrorCodes::OutOfEnergy);
+        if energy >= config.max_player_energy {
+            self.recharge_start_time = now_ts;
+        }
+        self.energy = 0;
+        Ok(energy)
+    }
+
+    pub fn spend_stake_energy(&mut self, energy_to_spend: u8) -> Result<()> {
+        self.energy = self.energy.checked_sub(energy_to_spend).ok_or(ErrorCodes::OutOfEnergy)?;
+        msg!("energy spent, remaining: {}", self.energy);
+        Ok(())
     }
 
     pub fn spend_energy(&mut self, energy_to_spend: u8) -> Result<()> {
         match self.player_identity.identity_type {
             IdentityType::None => err!(ErrorCodes::InvalidInput),
             IdentityType::Nft | 
-            IdentityType::MplCore => {
-                self.energy = self.energy.checked_sub(energy_to_spend).unwrap();
+            IdentityType::MplCore |
+            IdentityType::CompressedNft |
+            IdentityType::Token2022Nft => {
+                self.energy = self.energy.checked_sub(energy_to_spend).ok_or(ErrorCodes::OutOfEnergy)?;
                 msg!("energy spent, remaining: {}", self.energy);
                 Ok(())
             },
@@ -0,0 +1,285 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+use crate::{errors::ErrorCodes, CollectionVerification};
+
+mod bubblegum_program {
+    anchor_lang::declare_id!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+}
+mod spl_account_compression_program {
+    anchor_lang::declare_id!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+}
+mod mpl_account_compression_program {
+    anchor_lang::declare_id!("mcmt6YrQEMKw8Mw43FmpRLmf7BqRnFMKmAcbxE3xkAW");
+}
+
+pub const BUBBLEGUM_ID: Pubkey = bubblegum_program::ID;
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey = spl_account_compression_program::ID;
+pub const MPL_ACCOUNT_COMPRESSION_ID: Pubkey = mpl_account_compression_program::ID;
+
+/// account type byte, header version byte, then the V1 header
+const TREE_HEADER_SIZE: usize = 2 + 54;
+const CONCURRENT_MERKLE_TREE_ACCOUNT_TYPE: u8 = 1;
+const LEAF_SCHEMA_V1: u8 = 1;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CnftTokenStandard {
+    NonFungible,
+    FungibleAsset,
+    Fungible,
+    NonFungibleEdition,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CnftTokenProgramVersion {
+    Original,
+    Token2022,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CnftUseMethod {
+    Burn,
+    Multiple,
+    Single,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CnftUses {
+    pub use_method: CnftUseMethod,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CnftCollection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CnftCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+/// Bubblegum metadata as it was hashed into the leaf, field order matters
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct CnftMetadataArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<CnftTokenStandard>,
+    pub collection: Option<CnftCollection>,
+    pub uses: Option<CnftUses>,
+    pub token_program_version: CnftTokenProgramVersion,
+    pub creators: Vec<CnftCreator>,
+}
+
+impl CnftMetadataArgs {
+    pub fn data_hash(&self) -> Result<[u8; 32]> {
+        let metadata_args_hash = keccak::hashv(&[self.try_to_vec()?.as_slice()]);
+        Ok(keccak::hashv(&[
+            metadata_args_hash.as_ref(),
+            &self.seller_fee_basis_points.to_le_bytes(),
+        ]).to_bytes())
+    }
+
+    pub fn creator_hash(&self) -> [u8; 32] {
+        let creator_data = self.creators
+            .iter()
+            .map(|c| [c.address.as_ref(), &[c.verified as u8], &[c.share]].concat())
+            .collect::<Vec<_>>();
+        keccak::hashv(&creator_data.iter().map(|c| c.as_slice()).collect::<Vec<&[u8]>>()).to_bytes()
+    }
+
+    pub fn is_collection(&self, collection: &Pubkey, verification: CollectionVerification) -> bool {
+        (verification.allows_collection() && self.collection.is_some_and(|c| c.verified && c.key == *collection))
+            || (verification.allows_creator() && self.creators.iter().any(|c| c.verified && c.address == *collection))
+    }
+}
+
+/// Leaf preimage of a compressed NFT, the proof nodes are the first `proof_len` remaining accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct CompressedNftProof {
+    pub nonce: u64,
+    pub delegate: Pubkey,
+    pub metadata: CnftMetadataArgs,
+    pub proof_len: u8,
+}
+
+impl CompressedNftProof {
+    pub fn asset_id(&self, merkle_tree: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[b"asset", merkle_tree.as_ref(), &self.nonce.to_le_bytes()],
+            &BUBBLEGUM_ID,
+        ).0
+    }
+
+    fn leaf_hash(&self, asset_id: &Pubkey, owner: &Pubkey) -> Result<[u8; 32]> {
+        Ok(keccak::hashv(&[
+            &[LEAF_SCHEMA_V1],
+            asset_id.as_ref(),
+            owner.as_ref(),
+            self.delegate.as_ref(),
+            &self.nonce.to_le_bytes(),
+            &self.metadata.data_hash()?,
+            &self.metadata.creator_hash(),
+        ]).to_bytes())
+    }
+
+    /// Proves `owner` holds the asset in a Bubblegum tree and that it is a verified member of `collection`
+    pub fn verify(
+        &self,
+        merkle_tree: &AccountInfo,
+        owner: &Pubkey,
+        collection: &Pubkey,
+        verification: CollectionVerification,
+        remaining_accounts: &[AccountInfo],
+    ) -> Result<Pubkey> {
+        require!(self.metadata.is_collection(collection, verification), ErrorCodes::CollectionKeyMismatch);
+        require!(
+            *merkle_tree.owner == SPL_ACCOUNT_COMPRESSION_ID || *merkle_tree.owner == MPL_ACCOUNT_COMPRESSION_ID,
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        let proof_nodes = remaining_accounts
+            .get(..self.proof_len as usize)
+            .ok_or(ErrorCodes::CompressedProofInvalid)?
+            .iter()
+            .map(|a| a.key.to_bytes())
+            .collect::<Vec<[u8; 32]>>();
+
+        let data = merkle_tree.try_borrow_data()?;
+        let tree = ConcurrentMerkleTreeView::from_data(&data)?;
+        let (tree_authority, _) = Pubkey::find_program_address(&[merkle_tree.key.as_ref()], &BUBBLEGUM_ID);
+        require!(tree.authority == tree_authority, ErrorCodes::CompressedProofInvalid);
+
+        let asset_id = self.asset_id(merkle_tree.key);
+        let leaf = self.leaf_hash(&asset_id, owner)?;
+        // Bubblegum V1 leaves are appended in mint order, so the leaf index is the nonce
+        let index = u32::try_from(self.nonce).map_err(|_| ErrorCodes::CompressedProofInvalid)?;
+        require!(index >> tree.max_depth == 0, ErrorCodes::CompressedProofInvalid);
+        let mut proof = tree.complete_proof(proof_nodes, index)?;
+        require!(proof.len() == tree.max_depth as usize, ErrorCodes::CompressedProofInvalid);
+
+        // a proof fetched a few tree updates ago matches one of the buffered changelog roots,
+        // it is fast-forwarded through the newer changelogs like spl-account-compression does
+        let proof_root = root_from_proof(&leaf, &proof, index);
+        let age = tree.change_logs
+            .iter()
+            .position(|c| c.root == proof_root)
+            .ok_or(ErrorCodes::CompressedProofInvalid)?;
+        for change_log in tree.change_logs[..age].iter().rev() {
+            // the leaf itself was replaced since, the asset was transferred or burned
+            require!(change_log.index != index, ErrorCodes::CompressedProofInvalid);
+            let common_path_len = ((index ^ change_log.index) << (32 - tree.max_depth)).leading_zeros();
+            let critbit = (tree.max_depth - 1 - common_path_len) as usize;
+            proof[critbit] = change_log.path_node(critbit);
+        }
+        require!(root_from_proof(&leaf, &proof, index) == tree.change_logs[0].root, ErrorCodes::CompressedProofInvalid);
+        Ok(asset_id)
+    }
+}
+
+fn root_from_proof(leaf: &[u8; 32], proof: &[[u8; 32]], index: u32) -> [u8; 32] {
+    let mut node = *leaf;
+    for (level, sibling) in proof.iter().enumerate() {
+        node = match (index >> level) & 1 {
+            0 => keccak::hashv(&[&node, sibling]).to_bytes(),
+            _ => keccak::hashv(&[sibling, &node]).to_bytes(),
+        };
+    }
+    node
+}
+
+/// One buffered tree update, `path` holds the new nodes from the leaf up to the root
+struct ChangeLogView<'a> {
+    root: [u8; 32],
+    path: &'a [u8],
+    index: u32,
+}
+
+impl ChangeLogView<'_> {
+    fn path_node(&self, level: usize) -> [u8; 32] {
+        self.path[level * 32..level * 32 + 32].try_into().unwrap()
+    }
+}
+
+/// Just enough of a spl-account-compression tree to check a proof against its changelogs
+struct ConcurrentMerkleTreeView<'a> {
+    max_depth: u32,
+    authority: Pubkey,
+    /// buffered changelogs from the newest, whose root is the current root, to the oldest
+    change_logs: Vec<ChangeLogView<'a>>,
+    canopy: &'a [u8],
+}
+
+impl<'a> ConcurrentMerkleTreeView<'a> {
+    fn from_data(data: &'a [u8]) -> Result<Self> {
+        require!(data.len() > TREE_HEADER_SIZE, ErrorCodes::CompressedProofInvalid);
+        require!(data[0] == CONCURRENT_MERKLE_TREE_ACCOUNT_TYPE, ErrorCodes::CompressedProofInvalid);
+        let max_buffer_size = u32::from_le_bytes(data[2..6].try_into().unwrap()) as usize;
+        let max_depth = u32::from_le_bytes(data[6..10].try_into().unwrap());
+        let authority = Pubkey::new_from_array(data[10..42].try_into().unwrap());
+        require!((1..=30).contains(&max_depth), ErrorCodes::CompressedProofInvalid);
+
+        let node_path_size = 32 * max_depth as usize;
+        // root, path, index and padding
+        let change_log_size = 32 + node_path_size + 4 + 4;
+        // proof, leaf, index and padding
+        let rightmost_path_size = node_path_size + 32 + 4 + 4;
+        let tree_size = 8 + 8 + 8 + max_buffer_size * change_log_size + rightmost_path_size;
+        let tree = data
+            .get(TREE_HEADER_SIZE..TREE_HEADER_SIZE + tree_size)
+            .ok_or(ErrorCodes::CompressedProofInvalid)?;
+
+        let active_index = u64::from_le_bytes(tree[8..16].try_into().unwrap()) as usize;
+        let buffer_size = u64::from_le_bytes(tree[16..24].try_into().unwrap()) as usize;
+        require!(active_index < max_buffer_size && 0 < buffer_size && buffer_size <= max_buffer_size, ErrorCodes::CompressedProofInvalid);
+        // the changelog is a ring buffer whose newest entry sits at active_index
+        let change_logs = (0..buffer_size)
+            .map(|age| {
+                let offset = 24 + (active_index + max_buffer_size - age) % max_buffer_size * change_log_size;
+                let index_offset = offset + 32 + node_path_size;
+                ChangeLogView {
+                    root: tree[offset..offset + 32].try_into().unwrap(),
+                    path: &tree[offset + 32..index_offset],
+                    index: u32::from_le_bytes(tree[index_offset..index_offset + 4].try_into().unwrap()),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(ConcurrentMerkleTreeView {
+            max_depth,
+            authority,
+            change_logs,
+            canopy: &data[TREE_HEADER_SIZE + tree_size..],
+        })
+    }
+
+    /// Appends the upper proof nodes cached in the canopy, mirroring spl-account-compression
+    fn complete_proof(&self, mut proof: Vec<[u8; 32]>, index: u32) -> Result<Vec<[u8; 32]>> {
+        let canopy_nodes = self.canopy.len() / 32;
+        if canopy_nodes == 0 {
+            return Ok(proof);
+        }
+        let canopy_depth = (canopy_nodes + 2).ilog2() - 1;
+        let leaf_levels = self.max_depth.checked_sub(canopy_depth).ok_or(ErrorCodes::CompressedProofInvalid)?;
+        let mut node_index = ((1u64 << self.max_depth) + index as u64) >> leaf_levels;
+        let mut inferred_nodes: Vec<[u8; 32]> = vec![];
+        while node_index > 1 {
+            let shifted_index = node_index as usize - 2;
+            let cached_index = if shifted_index % 2 == 0 { shifted_index + 1 } else { shifted_index - 1 };
+            let start = cached_index * 32;
+            inferred_nodes.push(self.canopy[start..start + 32].try_into().unwrap());
+            node_index >>= 1;
+        }
+        let overlap = (proof.len() + inferred_nodes.len()).saturating_sub(self.max_depth as usize);
+        proof.extend(inferred_nodes.into_iter().skip(overlap));
+        Ok(proof)
+    }
+}
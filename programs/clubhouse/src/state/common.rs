@@ -1,14 +1,26 @@
-use crate::errors::ErrorCodes;
+use crate::{errors::ErrorCodes, CollectionVerification};
 use anchor_lang::prelude::*;
-use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::metadata::{
+    mpl_token_metadata::{
+        self,
+        accounts::TokenRecord,
+        types::{TokenDelegateRole, TokenStandard, TokenState},
+    },
+    MetadataAccount,
+};
+
+/// Checks the metadata is the canonical PDA of its mint and carries `pk` as a verified
+/// collection or creator, as allowed by `verification`
+pub fn metadata_is_collection(metadata: &Account<MetadataAccount>, pk: &Pubkey, verification: CollectionVerification) -> Result<()> {
+    let (canonical, _) = mpl_token_metadata::accounts::Metadata::find_pda(&metadata.mint);
+    require_keys_eq!(metadata.key(), canonical, ErrorCodes::MetadataMismatch);
 
-pub fn metadata_is_collection(metadata: &MetadataAccount, pk: &Pubkey) -> Result<()> {
     let collection = &metadata.collection;
-    if collection.is_some() && collection.as_ref().unwrap().verified && collection.as_ref().unwrap().key.eq(&pk)  {
+    if verification.allows_collection() && collection.is_some() && collection.as_ref().unwrap().verified && collection.as_ref().unwrap().key.eq(&pk)  {
         return Ok(());
     }
     let creators = &metadata.creators;
-    if creators.is_some() {
+    if verification.allows_creator() && creators.is_some() {
         for creator in creators.as_ref().unwrap() {
             if creator.verified && creator.address.eq(&pk) {
                 return Ok(());
@@ -17,6 +29,41 @@ pub fn metadata_is_collection(metadata: &MetadataAccount, pk: &Pubkey) -> Result
     }
     return err!(ErrorCodes::MetadataMismatch);
 }
+pub fn is_programmable(metadata: &MetadataAccount) -> bool {
+    matches!(
+        metadata.token_standard,
+        Some(TokenStandard::ProgrammableNonFungible) | Some(TokenStandard::ProgrammableNonFungibleEdition)
+    )
+}
+
+/// Loads the token record of a pNFT and rejects it while it is listed, locked or delegated for sale.
+/// Non programmable NFTs have no token record and pass through.
+pub fn validate_token_record(
+    metadata: &MetadataAccount,
+    token_account: &Pubkey,
+    token_record: Option<&AccountInfo>,
+) -> Result<Option<TokenRecord>> {
+    if !is_programmable(metadata) {
+        return Ok(None);
+    }
+    let token_record_info = token_record.ok_or(ErrorCodes::TokenRecordRequired)?;
+    require_keys_eq!(*token_record_info.owner, mpl_token_metadata::ID, ErrorCode::AccountOwnedByWrongProgram);
+    let (expected, _) = TokenRecord::find_pda(&metadata.mint, token_account);
+    require_keys_eq!(token_record_info.key(), expected, ErrorCodes::TokenRecordRequired);
+
+    let record = TokenRecord::from_bytes(&token_record_info.try_borrow_data()?)
+        .map_err(|_| ErrorCodes::TokenRecordRequired)?;
+    match record.state {
+        TokenState::Unlocked => {},
+        TokenState::Listed => return err!(ErrorCodes::AssetListed),
+        TokenState::Locked => return err!(ErrorCodes::AssetFrozen),
+    }
+    if record.delegate_role == Some(TokenDelegateRole::Sale) {
+        return err!(ErrorCodes::AssetListed);
+    }
+    Ok(Some(record))
+}
+
 pub fn string_len_borsh(text: &String) -> usize {
     4 + text.len()
 }
@@ -26,6 +73,10 @@ pub fn string_option_len(text: &Option<String>) -> usize {
     text.as_ref().map_or(0, |s| string_len_borsh(s))
 }
 
+pub fn vec_len_borsh(len: usize, item_space: usize) -> usize {
+    4 + len * item_space
+}
+
 pub fn validate_string(s: &str) -> Result<()> {
     if s.is_empty() {
         return Ok(());
@@ -72,85 +123,3 @@ fn is_allowed_char(c: char) -> bool {
                 '\'' | '"' | '-' | '_' | '@' | '#' | '$' | '%' | '&' | '*' | '+' | '=' | 
                 '<' | '>' | '/' | '\\' | '|' | '~' | '^')
 }
-
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum Key {
-    Uninitialized,
-    AssetV1,
-    HashedAssetV1,
-    PluginHeaderV1,
-    PluginRegistryV1,
-    CollectionV1,
-}
-
-impl Key {
-    pub fn from_u8(val: u8) -> Option<Self> {
-        match val {
-            0 => Some(Key::Uninitialized),
-            1 => Some(Key::AssetV1),
-            2 => Some(Key::HashedAssetV1),
-            3 => Some(Key::PluginHeaderV1),
-            4 => Some(Key::PluginRegistryV1),
-            5 => Some(Key::CollectionV1),
-            _ => None,
-        }
-    }
-}
-
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum UpdateAuthority {
-    None,
-    Address(Pubkey),
-    Collection(Pubkey),
-}
-
-#[derive(Clone, Debug)]
-pub struct SimplifiedAssetV1 {
-    pub key: Key,
-    pub owner: Pubkey,
-    pub update_authority: UpdateAuthority,
-}
-
-impl SimplifiedAssetV1 {
-    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self> {
-        let data = account_info.data.borrow();
-        
-        if data.len() < 34 {
-            return Err(error!(ErrorCode::AccountDidNotDeserialize));
-        }
-        
-        let key = Key::from_u8(data[0])
-            .ok_or(error!(ErrorCode::AccountDidNotDeserialize))?;
-        
-        if key != Key::AssetV1 {
-            return Err(error!(ErrorCode::AccountDidNotDeserialize));
-        }
-        
-        let owner = Pubkey::new_from_array(data[1..33].try_into().unwrap());
-        
-        let update_authority = match data[33] {
-            0 => UpdateAuthority::None,
-            1 => {
-                if data.len() < 66 {
-                    return Err(error!(ErrorCode::AccountDidNotDeserialize));
-                }
-                let pubkey = Pubkey::new_from_array(data[34..66].try_into().unwrap());
-                UpdateAuthority::Address(pubkey)
-            },
-            2 => {
-                if data.len() < 66 {
-                    return Err(error!(ErrorCode::AccountDidNotDeserialize));
-                }
-                let pubkey = Pubkey::new_from_array(data[34..66].try_into().unwrap());
-                UpdateAuthority::Collection(pubkey)
-            },
-            _ => return Err(error!(ErrorCode::AccountDidNotDeserialize)),
-        };
-        
-        Ok(SimplifiedAssetV1 {
-            key,
-            owner,
-            update_authority,
-        })
-    }
-}
\ No newline at end of file
@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+
+use crate::{HouseConfig, PlayerIdentity, TimeSpan, TokenUse};
+
+// Layouts written before accounts carried ACCOUNT_VERSION, only read by migrate_account.
+// The version byte of the current layouts sits where these hold `_reserved1[0]`,
+// the `nft_config` tag and the `stake_info` tag
+
+#[derive(AnchorDeserialize, Debug)]
+pub struct HouseV0 {
+    pub bump: u8,
+    pub house_admin: Pubkey,
+    pub manager_collection: Option<Pubkey>,
+    pub house_currency: Pubkey,
+    pub house_currency_decimals: u8,
+    pub total_campaigns: u32,
+    pub open_campaigns: u16,
+    pub unique_players: u32,
+    pub games_played: u32,
+    pub unclaimed_sol_fees: u64,
+    pub unclaimed_house_fees: u64,
+    pub is_active: bool,
+    pub _reserved1: [u64; 16],
+    pub config: HouseConfig,
+    pub _reserved2: [u64; 16],
+    pub house_name: String,
+    pub uri: Option<String>,
+}
+
+#[derive(AnchorDeserialize, Debug)]
+pub struct CampaignV0 {
+    pub auth_bump: u8,
+    pub house: Pubkey,
+    pub creator: Pubkey,
+    pub manager_identity: PlayerIdentity,
+    pub reward_mint: Pubkey,
+    pub reward_mint_decimals: u8,
+    pub max_rewards_per_game: u64,
+    pub rewards_claim_fee: u64,
+    pub player_count: u32,
+    pub active_games: u32,
+    pub total_games: u32,
+    pub time_span: TimeSpan,
+    pub house_config_snapshot: HouseConfig,
+    pub nft_config: Option<NftCampaignConfigV0>,
+    pub unclaimed_sol_fees: u64,
+    pub _reserved_config: [u64; 7],
+    pub token_config: Option<TokenCampaignConfigV0>,
+    pub _reserved_for_token: [u64; 2],
+    pub _reserved_bytes: [u8; 7],
+    pub burn_remainder: bool,
+    pub rewards_available: u64,
+    pub reserved_rewards: u64,
+    pub campaign_name: String,
+    pub uri: Option<String>,
+}
+
+#[derive(AnchorDeserialize, Clone, Copy, Debug)]
+pub struct NftCampaignConfigV0 {
+    pub collection: Pubkey,
+    pub max_player_energy: u8,
+    pub energy_recharge_minutes: Option<i64>,
+}
+
+#[derive(AnchorDeserialize, Clone, Copy, Debug)]
+pub struct TokenCampaignConfigV0 {
+    pub spending_mint: Pubkey,
+    pub energy_price: u64,
+    pub spending_mint_decimals: u8,
+    /// Stake, Burn and Pay kept their indexes
+    pub token_use: TokenUse,
+}
+
+#[derive(AnchorDeserialize, Debug)]
+pub struct CampaignPlayerV0 {
+    pub player_identity: PlayerIdentity,
+    pub campaign: Pubkey,
+    pub house: Pubkey,
+    pub energy: u8,
+    pub recharge_start_time: i64,
+    pub game_start_time: i64,
+    pub games_played: u32,
+    pub in_game: bool,
+    pub rewards_claimed: u64,
+    pub stake_info: Option<StakeInfoV0>,
+}
+
+#[derive(AnchorDeserialize, Debug)]
+pub struct StakeInfoV0 {
+    pub amount: u64,
+    pub campaign_end_time: i64,
+    pub staked_mint: Pubkey,
+    pub staked_mint_decimals: u8,
+    pub campaign_name: String,
+}
@@ -1,5 +1,13 @@
 pub mod accounts;
 pub mod common;
+pub mod mpl_core;
+pub mod bubblegum;
+pub mod legacy;
+pub mod token_group;
 
 pub use accounts::*;
-pub use common::*;
\ No newline at end of file
+pub use common::*;
+pub use mpl_core::*;
+pub use bubblegum::*;
+pub use legacy::*;
+pub use token_group::*;
@@ -0,0 +1,331 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+
+use crate::errors::ErrorCodes;
+
+mod mpl_core_program {
+    anchor_lang::declare_id!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+}
+
+pub const MPL_CORE_ID: Pubkey = mpl_core_program::ID;
+
+#[derive(AnchorDeserialize, Clone, Debug, Eq, PartialEq)]
+pub enum Key {
+    Uninitialized,
+    AssetV1,
+    HashedAssetV1,
+    PluginHeaderV1,
+    PluginRegistryV1,
+    CollectionV1,
+}
+
+#[derive(AnchorDeserialize, Clone, Debug, Eq, PartialEq)]
+pub enum UpdateAuthority {
+    None,
+    Address(Pubkey),
+    Collection(Pubkey),
+}
+
+/// Authority of a plugin as stored in the registry
+#[derive(AnchorDeserialize, Clone, Debug, Eq, PartialEq)]
+pub enum PluginAuthority {
+    None,
+    Owner,
+    UpdateAuthority,
+    Address { address: Pubkey },
+}
+
+/// Discriminants of the plugins we read, the registry stores them as a u8
+pub struct PluginType;
+
+impl PluginType {
+    pub const FREEZE_DELEGATE: u8 = 1;
+    pub const TRANSFER_DELEGATE: u8 = 3;
+    pub const UPDATE_DELEGATE: u8 = 4;
+    pub const PERMANENT_FREEZE_DELEGATE: u8 = 5;
+    pub const ATTRIBUTES: u8 = 6;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Eq, PartialEq)]
+pub struct Attribute {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(AnchorDeserialize)]
+struct BaseAssetV1 {
+    key: Key,
+    owner: Pubkey,
+    update_authority: UpdateAuthority,
+    name: String,
+    uri: String,
+    _seq: Option<u64>,
+}
+
+#[derive(AnchorDeserialize)]
+struct BaseCollectionV1 {
+    key: Key,
+    update_authority: Pubkey,
+    name: String,
+    uri: String,
+    _num_minted: u32,
+    _current_size: u32,
+}
+
+#[derive(AnchorDeserialize)]
+struct PluginHeaderV1 {
+    key: Key,
+    plugin_registry_offset: u64,
+}
+
+#[derive(AnchorDeserialize)]
+struct RegistryRecord {
+    plugin_type: u8,
+    authority: PluginAuthority,
+    offset: u64,
+}
+
+/// Only the internal registry is read, external plugin adapters follow it and are ignored
+#[derive(AnchorDeserialize)]
+struct PluginRegistryV1 {
+    key: Key,
+    registry: Vec<RegistryRecord>,
+}
+
+/// The plugins that matter to campaigns, collected from the plugin registry
+#[derive(Clone, Debug, Default)]
+pub struct CorePlugins {
+    pub frozen: bool,
+    pub freeze_delegate: Option<PluginAuthority>,
+    pub permanently_frozen: bool,
+    pub transfer_delegate: Option<PluginAuthority>,
+    pub update_delegates: Vec<Pubkey>,
+    pub attributes: Vec<Attribute>,
+}
+
+impl CorePlugins {
+    fn from_data(data: &[u8], header_start: usize) -> Result<Self> {
+        let mut plugins = CorePlugins::default();
+        if header_start >= data.len() {
+            return Ok(plugins);
+        }
+        let header = PluginHeaderV1::deserialize(&mut &data[header_start..]).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+        require!(header.key == Key::PluginHeaderV1, ErrorCode::AccountDidNotDeserialize);
+
+        let registry_data = data.get(header.plugin_registry_offset as usize..).ok_or(error!(ErrorCode::AccountDidNotDeserialize))?;
+        let registry = PluginRegistryV1::deserialize(&mut &registry_data[..]).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+        require!(registry.key == Key::PluginRegistryV1, ErrorCode::AccountDidNotDeserialize);
+
+        for record in registry.registry {
+            let mut plugin_data = data.get(record.offset as usize..).ok_or(error!(ErrorCode::AccountDidNotDeserialize))?;
+            // every plugin is prefixed by its enum discriminant
+            let plugin_type = u8::deserialize(&mut plugin_data).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+            require!(plugin_type == record.plugin_type, ErrorCode::AccountDidNotDeserialize);
+            match plugin_type {
+                PluginType::FREEZE_DELEGATE => {
+                    plugins.frozen |= bool::deserialize(&mut plugin_data).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+                    plugins.freeze_delegate = Some(record.authority);
+                },
+                PluginType::PERMANENT_FREEZE_DELEGATE => {
+                    plugins.permanently_frozen |= bool::deserialize(&mut plugin_data).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+                },
+                PluginType::TRANSFER_DELEGATE => {
+                    plugins.transfer_delegate = Some(record.authority);
+                },
+                PluginType::UPDATE_DELEGATE => {
+                    if let PluginAuthority::Address { address } = record.authority {
+                        plugins.update_delegates.push(address);
+                    }
+                    let additional_delegates = Vec::<Pubkey>::deserialize(&mut plugin_data).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+                    plugins.update_delegates.extend(additional_delegates);
+                },
+                PluginType::ATTRIBUTES => {
+                    plugins.attributes = Vec::<Attribute>::deserialize(&mut plugin_data).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+                },
+                _ => {},
+            }
+        }
+        Ok(plugins)
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen || self.permanently_frozen
+    }
+
+    /// The FreezeDelegate plugin is held by `delegate`, frozen or not
+    pub fn is_freeze_delegate(&self, delegate: &Pubkey) -> bool {
+        matches!(self.freeze_delegate, Some(PluginAuthority::Address { address }) if address == *delegate)
+    }
+
+    /// Escrowless marketplace listings hand the transfer delegate to the marketplace
+    pub fn is_listed(&self) -> bool {
+        matches!(self.transfer_delegate, Some(PluginAuthority::Address { .. }))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CoreAsset {
+    pub key: Key,
+    pub owner: Pubkey,
+    pub update_authority: UpdateAuthority,
+    pub name: String,
+    pub uri: String,
+    pub plugins: CorePlugins,
+}
+
+impl CoreAsset {
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self> {
+        require!(*account_info.owner == MPL_CORE_ID, ErrorCode::AccountOwnedByWrongProgram);
+        let data = account_info.data.borrow();
+        let mut cursor: &[u8] = &data;
+        let base = BaseAssetV1::deserialize(&mut cursor).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+        require!(base.key == Key::AssetV1, ErrorCode::AccountDidNotDeserialize);
+        let plugins = CorePlugins::from_data(&data, data.len() - cursor.len())?;
+
+        Ok(CoreAsset {
+            key: base.key,
+            owner: base.owner,
+            update_authority: base.update_authority,
+            name: base.name,
+            uri: base.uri,
+            plugins,
+        })
+    }
+
+    /// Accepts assets in the collection itself, or whose collection or update authority
+    /// is managed by `authority` directly or through an update delegate
+    pub fn verify_collection(&self, authority: &Pubkey, collection: Option<&CoreCollection>) -> Result<()> {
+        match (&self.update_authority, collection) {
+            (UpdateAuthority::Collection(key), _) if key == authority => Ok(()),
+            (UpdateAuthority::Collection(key), Some(collection)) if *key == collection.key => {
+                require!(collection.is_managed_by(authority), ErrorCodes::CollectionKeyMismatch);
+                Ok(())
+            },
+            (UpdateAuthority::Address(address), None) if address == authority || self.plugins.update_delegates.contains(authority) => Ok(()),
+            (_, _) => err!(ErrorCodes::CollectionKeyMismatch),
+        }
+    }
+
+    /// Frozen or listed assets cannot start games or act as credentials
+    pub fn ensure_unlocked(&self, collection: Option<&CoreCollection>) -> Result<()> {
+        require!(!self.plugins.is_frozen(), ErrorCodes::AssetFrozen);
+        require!(!collection.is_some_and(|c| c.plugins.is_frozen()), ErrorCodes::AssetFrozen);
+        require!(!self.plugins.is_listed(), ErrorCodes::AssetListed);
+        Ok(())
+    }
+
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.plugins.attributes.iter().find(|a| a.key == key).map(|a| a.value.as_str())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CoreCollection {
+    pub key: Pubkey,
+    pub update_authority: Pubkey,
+    pub name: String,
+    pub uri: String,
+    pub plugins: CorePlugins,
+}
+
+impl CoreCollection {
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self> {
+        require!(*account_info.owner == MPL_CORE_ID, ErrorCode::AccountOwnedByWrongProgram);
+        let data = account_info.data.borrow();
+        let mut cursor: &[u8] = &data;
+        let base = BaseCollectionV1::deserialize(&mut cursor).map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+        require!(base.key == Key::CollectionV1, ErrorCode::AccountDidNotDeserialize);
+        let plugins = CorePlugins::from_data(&data, data.len() - cursor.len())?;
+
+        Ok(CoreCollection {
+            key: *account_info.key,
+            update_authority: base.update_authority,
+            name: base.name,
+            uri: base.uri,
+            plugins,
+        })
+    }
+
+    pub fn is_managed_by(&self, authority: &Pubkey) -> bool {
+        self.update_authority == *authority || self.plugins.update_delegates.contains(authority)
+    }
+}
+
+/// Discriminants of the mpl-core instructions we invoke
+struct CoreInstruction;
+
+impl CoreInstruction {
+    const ADD_PLUGIN_V1: u8 = 2;
+    const REMOVE_PLUGIN_V1: u8 = 4;
+    const UPDATE_PLUGIN_V1: u8 = 6;
+}
+
+/// AddPluginV1, RemovePluginV1 and UpdatePluginV1 share the same account list,
+/// absent optional accounts are passed as the program id
+fn plugin_instruction(data: Vec<u8>, asset: &Pubkey, collection: Option<&Pubkey>, payer: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: MPL_CORE_ID,
+        accounts: vec![
+            AccountMeta::new(*asset, false),
+            collection.map_or(AccountMeta::new_readonly(MPL_CORE_ID, false), |c| AccountMeta::new(*c, false)),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(System::id(), false),
+            AccountMeta::new_readonly(MPL_CORE_ID, false),
+        ],
+        data,
+    }
+}
+
+/// Adds a frozen FreezeDelegate plugin whose authority is `delegate`, signed by the asset owner
+pub fn freeze_asset_instruction(asset: &Pubkey, collection: Option<&Pubkey>, owner: &Pubkey, delegate: &Pubkey) -> Instruction {
+    // Plugin::FreezeDelegate { frozen: true }, Some(Authority::Address { address: delegate })
+    let mut data = vec![CoreInstruction::ADD_PLUGIN_V1, PluginType::FREEZE_DELEGATE, 1, 1, 3];
+    data.extend_from_slice(delegate.as_ref());
+    plugin_instruction(data, asset, collection, owner, owner)
+}
+
+/// Freezes or thaws an existing FreezeDelegate plugin, signed by its `delegate` authority
+pub fn update_freeze_instruction(asset: &Pubkey, collection: Option<&Pubkey>, payer: &Pubkey, delegate: &Pubkey, frozen: bool) -> Instruction {
+    let data = vec![CoreInstruction::UPDATE_PLUGIN_V1, PluginType::FREEZE_DELEGATE, frozen as u8];
+    plugin_instruction(data, asset, collection, payer, delegate)
+}
+
+/// Removes the thawed FreezeDelegate plugin, signed by the asset owner
+pub fn remove_freeze_instruction(asset: &Pubkey, collection: Option<&Pubkey>, owner: &Pubkey) -> Instruction {
+    let data = vec![CoreInstruction::REMOVE_PLUGIN_V1, PluginType::FREEZE_DELEGATE];
+    plugin_instruction(data, asset, collection, owner, owner)
+}
+
+/// Loads a player or manager asset, checking ownership and collection membership
+pub fn load_core_asset(
+    asset_info: &AccountInfo,
+    collection_info: Option<&AccountInfo>,
+    owner: &Pubkey,
+    collection_authority: &Pubkey,
+) -> Result<(CoreAsset, Option<CoreCollection>)> {
+    let (asset, collection) = load_owned_core_asset(asset_info, collection_info, owner)?;
+    asset.verify_collection(collection_authority, collection.as_ref())?;
+    Ok((asset, collection))
+}
+
+/// Loads an asset and its collection, checking ownership only
+pub fn load_owned_core_asset(
+    asset_info: &AccountInfo,
+    collection_info: Option<&AccountInfo>,
+    owner: &Pubkey,
+) -> Result<(CoreAsset, Option<CoreCollection>)> {
+    let asset = CoreAsset::from_account_info(asset_info)?;
+    require!(asset.owner == *owner, ErrorCodes::TokenOwnerMismatch);
+    // assets in a collection must come with that collection so its freeze plugins are always seen
+    let collection = match (&asset.update_authority, collection_info) {
+        (UpdateAuthority::Collection(key), Some(collection_info)) => {
+            require_keys_eq!(*key, *collection_info.key, ErrorCodes::CollectionKeyMismatch);
+            Some(CoreCollection::from_account_info(collection_info)?)
+        },
+        (UpdateAuthority::Collection(_), None) => return err!(ErrorCodes::CoreCollectionRequired),
+        (_, Some(_)) => return err!(ErrorCodes::CollectionKeyMismatch),
+        (_, None) => None,
+    };
+    Ok((asset, collection))
+}
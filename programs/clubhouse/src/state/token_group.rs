@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::{spl_token_2022::{self, extension::{BaseStateWithExtensions, StateWithExtensions}}, Token2022};
+use spl_token_group_interface::state::TokenGroupMember;
+
+use crate::errors::ErrorCodes;
+
+/// Verifies a Token-2022 NFT mint belongs to `group` through its group member extension.
+/// Only the group update authority can initialize a member, so the group pointer can be trusted
+pub fn verify_group_member(mint_info: &AccountInfo, group: &Pubkey) -> Result<()> {
+    require_keys_eq!(*mint_info.owner, Token2022::id(), ErrorCode::AccountOwnedByWrongProgram);
+    let data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    require!(mint.base.decimals == 0 && mint.base.supply == 1, ErrorCodes::OwnerBalanceMismatch);
+
+    let member = mint.get_extension::<TokenGroupMember>().map_err(|_| ErrorCodes::CollectionProofInvalid)?;
+    require_keys_eq!(member.mint, mint_info.key(), ErrorCodes::CollectionProofInvalid);
+    require_keys_eq!(member.group, *group, ErrorCodes::CollectionKeyMismatch);
+    Ok(())
+}
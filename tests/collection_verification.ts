@@ -0,0 +1,59 @@
+import { expect } from "chai";
+import {
+  createCampaign,
+  createHouse,
+  createMetadataCollection,
+  createMetadataNft,
+  endGame,
+  expectError,
+  metadataAccounts,
+  newUser,
+  nftConfig,
+  playerPda,
+  program,
+  startGame,
+  wallet,
+} from "./helpers";
+
+describe("collection verification", () => {
+  it("checks the verified collection or creator the campaign asks for", async () => {
+    const setup = await createHouse();
+    const collection = await createMetadataCollection();
+    const otherCollection = await createMetadataCollection();
+    const user = await newUser();
+    // the wallet is the verified creator of both
+    const member = await createMetadataNft(user.publicKey, { collection });
+    const outsider = await createMetadataNft(user.publicKey, { collection: otherCollection });
+    const play = async (campaign: any, nft: typeof member) => {
+      const game = { user, identity: nft.mint, accounts: metadataAccounts(nft) };
+      await startGame(setup, campaign, game);
+      await endGame(setup, campaign, { ...game, amountWon: 0 });
+    };
+
+    const collectionOnly = await createCampaign(setup, {
+      nftConfig: nftConfig(collection, { collectionVerification: { collection: {} } }),
+    });
+    await play(collectionOnly, member);
+    await expectError(play(collectionOnly, outsider), "CollectionKeyMismatch");
+
+    // a creator that minted several collections unlocks all of them, unless the collection is required
+    const creatorOnly = await createCampaign(setup, {
+      nftConfig: nftConfig(wallet.publicKey, { collectionVerification: { creator: {} } }),
+    });
+    await play(creatorOnly, member);
+    await play(creatorOnly, outsider);
+    const creatorAsCollection = await createCampaign(setup, {
+      nftConfig: nftConfig(wallet.publicKey, { collectionVerification: { collection: {} } }),
+    });
+    await expectError(play(creatorAsCollection, member), "CollectionKeyMismatch");
+    const collectionAsCreator = await createCampaign(setup, {
+      nftConfig: nftConfig(collection, { collectionVerification: { creator: {} } }),
+    });
+    await expectError(play(collectionAsCreator, member), "CollectionKeyMismatch");
+
+    const either = await createCampaign(setup, { nftConfig: nftConfig(collection) });
+    await play(either, member);
+    const player = await program.account.campaignPlayer.fetch(playerPda(either.campaign, member.mint));
+    expect(player.gamesPlayed).to.equal(1);
+  });
+});
@@ -0,0 +1,128 @@
+import { BN } from "@coral-xyz/anchor";
+import { generateSigner, none, publicKey, some } from "@metaplex-foundation/umi";
+import {
+  MetadataArgsArgs,
+  TokenProgramVersion,
+  TokenStandard,
+  createTree,
+  findLeafAssetIdPda,
+  getMerkleProof,
+  hashLeaf,
+  mintToCollectionV1,
+  mplBubblegum,
+} from "@metaplex-foundation/mpl-bubblegum";
+import { fromWeb3JsPublicKey, toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  createCampaign,
+  createHouse,
+  createMetadataCollection,
+  endGame,
+  expectError,
+  newUser,
+  nftConfig,
+  playerPda,
+  program,
+  startGame,
+  umi,
+} from "./helpers";
+
+const MAX_DEPTH = 5;
+
+describe("compressed nfts", () => {
+  const bubblegum = umi.use(mplBubblegum());
+
+  /** Mints a cNFT of `collectionMint` to `owner`, returning the leaf preimage start_game proves */
+  const mintLeaf = async (merkleTree: PublicKey, collectionMint: PublicKey, owner: PublicKey, leafIndex: number) => {
+    const metadata: MetadataArgsArgs = {
+      name: `player ${leafIndex}`,
+      symbol: "",
+      uri: "",
+      sellerFeeBasisPoints: 0,
+      primarySaleHappened: false,
+      isMutable: true,
+      editionNonce: none(),
+      tokenStandard: some(TokenStandard.NonFungible),
+      collection: some({ key: fromWeb3JsPublicKey(collectionMint), verified: false }),
+      uses: none(),
+      tokenProgramVersion: TokenProgramVersion.Original,
+      creators: [],
+    };
+    await mintToCollectionV1(bubblegum, {
+      leafOwner: fromWeb3JsPublicKey(owner),
+      merkleTree: fromWeb3JsPublicKey(merkleTree),
+      collectionMint: fromWeb3JsPublicKey(collectionMint),
+      metadata,
+    }).sendAndConfirm(bubblegum);
+    const verified = { ...metadata, collection: some({ key: fromWeb3JsPublicKey(collectionMint), verified: true }) };
+    const leaf = hashLeaf(bubblegum, {
+      merkleTree: fromWeb3JsPublicKey(merkleTree),
+      owner: fromWeb3JsPublicKey(owner),
+      leafIndex,
+      metadata: verified,
+    });
+    const [assetId] = findLeafAssetIdPda(bubblegum, { merkleTree: fromWeb3JsPublicKey(merkleTree), leafIndex });
+    return { leaf: publicKey(leaf), assetId: toWeb3JsPublicKey(assetId), name: metadata.name as string };
+  };
+
+  /** The start_game/end_game proof argument, with the sibling nodes as remaining accounts */
+  const proofFor = (leaves: any[], index: number, owner: PublicKey, name: string, collectionMint: PublicKey) => {
+    const nodes = getMerkleProof(leaves, MAX_DEPTH, leaves[index], index);
+    return {
+      compressedNft: {
+        nonce: new BN(index),
+        delegate: owner,
+        metadata: {
+          name,
+          symbol: "",
+          uri: "",
+          sellerFeeBasisPoints: 0,
+          primarySaleHappened: false,
+          isMutable: true,
+          editionNonce: null,
+          tokenStandard: { nonFungible: {} },
+          collection: { verified: true, key: collectionMint },
+          uses: null,
+          tokenProgramVersion: { original: {} },
+          creators: [],
+        },
+        proofLen: nodes.length,
+      },
+      remainingAccounts: nodes.map((node) => ({ pubkey: toWeb3JsPublicKey(node), isSigner: false, isWritable: false })),
+    };
+  };
+
+  it("plays with a cNFT proven against the current or a recent tree root", async () => {
+    const collectionMint = await createMetadataCollection();
+    const tree = generateSigner(bubblegum);
+    await (await createTree(bubblegum, { merkleTree: tree, maxDepth: MAX_DEPTH, maxBufferSize: 8 })).sendAndConfirm(bubblegum);
+    const merkleTree = toWeb3JsPublicKey(tree.publicKey);
+
+    const setup = await createHouse();
+    const campaign = await createCampaign(setup, { nftConfig: nftConfig(collectionMint, { allowCompressedNfts: true }) });
+    const user = await newUser();
+    const first = await mintLeaf(merkleTree, collectionMint, user.publicKey, 0);
+    // proof of the first leaf taken before the second mint changed the root
+    const staleProof = proofFor([first.leaf], 0, user.publicKey, first.name, collectionMint);
+    const second = await mintLeaf(merkleTree, collectionMint, user.publicKey, 1);
+
+    const game = { user, identity: first.assetId, accounts: { merkleTree }, ...staleProof };
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { ...game, amountWon: 3 });
+    const player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, first.assetId));
+    expect(player.playerIdentity.identityType).to.deep.equal({ compressedNft: {} });
+
+    // someone else cannot prove the leaf as theirs
+    const thief = await newUser();
+    await expectError(startGame(setup, campaign, { ...game, user: thief }), "CompressedProofInvalid");
+
+    const secondProof = proofFor([first.leaf, second.leaf], 1, user.publicKey, second.name, collectionMint);
+    await startGame(setup, campaign, { user, identity: second.assetId, accounts: { merkleTree }, ...secondProof });
+    const tampered = { ...secondProof, remainingAccounts: [{ pubkey: Keypair.generate().publicKey, isSigner: false, isWritable: false }, ...secondProof.remainingAccounts.slice(1)] };
+    await expectError(
+      endGame(setup, campaign, { user, identity: second.assetId, accounts: { merkleTree }, ...tampered, amountWon: 0 }),
+      "CompressedProofInvalid"
+    );
+  });
+});
@@ -0,0 +1,45 @@
+import { expect } from "chai";
+import {
+  coreAccounts,
+  createCampaign,
+  createCoreAsset,
+  createCoreCollection,
+  createHouse,
+  endGame,
+  expectError,
+  newUser,
+  now,
+  nftConfig,
+  playerPda,
+  program,
+  startGame,
+  waitUntil,
+} from "./helpers";
+
+describe("energy reset", () => {
+  it("refills energy at the next period boundary", async () => {
+    const setup = await createHouse();
+    const collection = await createCoreCollection();
+    const campaign = await createCampaign(setup, {
+      nftConfig: nftConfig(collection, { maxPlayerEnergy: 1, energyReset: { periodMinutes: 1, epoch: 0 } }),
+    });
+    const user = await newUser();
+    const asset = await createCoreAsset(collection, user.publicKey);
+    const game = { user, identity: asset, accounts: coreAccounts(asset, collection) };
+
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { ...game, amountWon: 10 });
+    const campaignPlayer = playerPda(campaign.campaign, asset);
+    const { lastResetPeriod, energy } = await program.account.campaignPlayer.fetch(campaignPlayer);
+    expect(energy).to.equal(0);
+
+    // no energy left until the period rolls over, and no panic on the way
+    await expectError(startGame(setup, campaign, game), "OutOfEnergy");
+
+    await waitUntil((Math.floor((await now()) / 60) + 1) * 60 + 1);
+    await startGame(setup, campaign, game);
+    const player = await program.account.campaignPlayer.fetch(campaignPlayer);
+    expect(player.lastResetPeriod.toNumber()).to.be.greaterThan(lastResetPeriod.toNumber());
+    expect(player.inGame).to.equal(true);
+  });
+});
@@ -0,0 +1,78 @@
+import { Keypair } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  ACCOUNT_VERSION,
+  createCampaign,
+  createHouse,
+  depositVaultPda,
+  endGame,
+  expectError,
+  fundedAccount,
+  gameMode,
+  migrateAccount,
+  newMint,
+  newUser,
+  payingAccounts,
+  playerPda,
+  program,
+  startGame,
+  tokenBalance,
+  tokenConfig,
+} from "./helpers";
+
+describe("game modes", () => {
+  it("charges, reserves and settles each game by its mode", async () => {
+    const setup = await createHouse();
+    const gameMint = await newMint();
+    const modeOracle = Keypair.generate();
+    const campaign = await createCampaign(setup, {
+      gameMint,
+      tokenConfig: tokenConfig(gameMint, 10, { pay: {} }),
+      gameModes: [gameMode(1, 10, 50), gameMode(2, 25, 200), gameMode(1, 5, 20, modeOracle.publicKey)],
+    });
+    const user = await newUser();
+    const deposit = await fundedAccount(gameMint, user, 1_000);
+
+    await expectError(
+      startGame(setup, campaign, { user, gameMode: 3, accounts: payingAccounts(campaign, deposit) }),
+      "InvalidGameMode"
+    );
+
+    await startGame(setup, campaign, { user, gameMode: 1, accounts: payingAccounts(campaign, deposit) });
+    expect(await tokenBalance(deposit)).to.equal(975);
+    expect(await tokenBalance(depositVaultPda(campaign.campaign))).to.equal(25);
+    let state = await program.account.campaign.fetch(campaign.campaign);
+    expect(state.reservedRewards.toNumber()).to.equal(200);
+
+    await expectError(endGame(setup, campaign, { user, amountWon: 201 }), "AmountTooHigh");
+    await endGame(setup, campaign, { user, amountWon: 150 });
+    state = await program.account.campaign.fetch(campaign.campaign);
+    expect(state.reservedRewards.toNumber()).to.equal(0);
+    const player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, user.publicKey));
+    expect(player.gameMode).to.equal(1);
+    expect(player.rewardsClaimed.toNumber()).to.equal(150);
+
+    // the mode oracle replaces the house oracle
+    await startGame(setup, campaign, { user, gameMode: 2, accounts: payingAccounts(campaign, deposit) });
+    await expectError(endGame(setup, campaign, { user, amountWon: 0 }), "OracleMismatch");
+    await endGame(setup, campaign, { user, amountWon: 20, oracle: modeOracle });
+  });
+
+  it("writes the account version and refuses to migrate current accounts", async () => {
+    const setup = await createHouse();
+    const gameMint = await newMint();
+    const campaign = await createCampaign(setup, { gameMint, tokenConfig: tokenConfig(gameMint, 1, { burn: {} }) });
+    const user = await newUser();
+    await startGame(setup, campaign, { user, accounts: payingAccounts(campaign, await fundedAccount(gameMint, user, 10)) });
+    const campaignPlayer = playerPda(campaign.campaign, user.publicKey);
+
+    expect((await program.account.house.fetch(setup.house)).version).to.equal(ACCOUNT_VERSION);
+    expect((await program.account.campaign.fetch(campaign.campaign)).version).to.equal(ACCOUNT_VERSION);
+    expect((await program.account.campaignPlayer.fetch(campaignPlayer)).version).to.equal(ACCOUNT_VERSION);
+
+    for (const legacyAccount of [setup.house, campaign.campaign]) {
+      await expectError(migrateAccount(legacyAccount), "AccountAlreadyMigrated");
+    }
+    await expectError(migrateAccount(campaignPlayer, campaign.campaign), "AccountAlreadyMigrated");
+  });
+});
@@ -0,0 +1,814 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram, Transaction, TransactionInstruction, sendAndConfirmTransaction } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  createInitializeGroupMemberPointerInstruction,
+  createInitializeGroupPointerInstruction,
+  createInitializeMintInstruction,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+  mintTo,
+  tokenGroupInitializeGroupWithRentTransfer,
+  tokenGroupMemberInitializeWithRentTransfer,
+} from "@solana/spl-token";
+import { createUmi } from "@metaplex-foundation/umi-bundle-defaults";
+import { generateSigner, keypairIdentity, percentAmount, some } from "@metaplex-foundation/umi";
+import { fromWeb3JsKeypair, fromWeb3JsPublicKey, toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { create, createCollection, fetchCollection, mplCore } from "@metaplex-foundation/mpl-core";
+import {
+  TokenStandard,
+  createNft,
+  createProgrammableNft,
+  findMasterEditionPda,
+  findMetadataPda,
+  findTokenRecordPda,
+  mplTokenMetadata,
+  verifyCollectionV1,
+} from "@metaplex-foundation/mpl-token-metadata";
+import { expect } from "chai";
+import { Clubhouse } from "../target/types/clubhouse";
+
+anchor.setProvider(anchor.AnchorProvider.env());
+
+export const provider = anchor.getProvider() as anchor.AnchorProvider;
+export const connection = provider.connection;
+export const program = anchor.workspace.Clubhouse as Program<Clubhouse>;
+export const wallet = (provider.wallet as anchor.Wallet).payer;
+
+export const umi = createUmi(connection.rpcEndpoint, "confirmed")
+  .use(mplCore())
+  .use(mplTokenMetadata())
+  .use(keypairIdentity(fromWeb3JsKeypair(wallet)));
+
+export const ACCOUNT_VERSION = 2;
+
+export const pda = (...seeds: (Buffer | Uint8Array)[]) =>
+  PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+export const housePda = (name: string) => pda(Buffer.from("house"), Buffer.from(name));
+export const houseVaultPda = (house: PublicKey) => pda(Buffer.from("vault"), house.toBuffer());
+export const campaignAuthPda = (campaign: PublicKey) => pda(campaign.toBuffer());
+export const rewardVaultPda = (campaign: PublicKey) => pda(Buffer.from("rewards"), campaign.toBuffer());
+export const depositVaultPda = (campaign: PublicKey) => pda(Buffer.from("player_deposit"), campaign.toBuffer());
+export const solDepositPda = (campaign: PublicKey) => pda(Buffer.from("sol_deposit"), campaign.toBuffer());
+export const solRewardsPda = (campaign: PublicKey) => pda(Buffer.from("sol_rewards"), campaign.toBuffer());
+export const energyMintPda = (campaign: PublicKey) => pda(Buffer.from("energy_mint"), campaign.toBuffer());
+export const playerPda = (campaign: PublicKey, identity: PublicKey) =>
+  pda(Buffer.from("player"), campaign.toBuffer(), identity.toBuffer());
+export const housePlayerPda = (house: PublicKey, identity: PublicKey) =>
+  pda(Buffer.from("house_player"), house.toBuffer(), identity.toBuffer());
+export const managerSlotPda = (house: PublicKey, pass: PublicKey) =>
+  pda(Buffer.from("manager_slot"), house.toBuffer(), pass.toBuffer());
+export const slashPda = (campaignPlayer: PublicKey) => pda(Buffer.from("slash"), campaignPlayer.toBuffer());
+
+let nameCounter = 0;
+export const uniqueName = (prefix: string) => `${prefix} ${Date.now() % 1_000_000} ${nameCounter++}`;
+
+export const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+export const now = async () => {
+  const slot = await connection.getSlot();
+  return (await connection.getBlockTime(slot)) ?? Math.floor(Date.now() / 1000);
+};
+
+export const waitUntil = async (timestamp: number) => {
+  while ((await now()) <= timestamp) {
+    await sleep(500);
+  }
+};
+
+export const airdrop = async (to: PublicKey, sol = 10) => {
+  const signature = await connection.requestAirdrop(to, sol * LAMPORTS_PER_SOL);
+  await connection.confirmTransaction({ signature, ...(await connection.getLatestBlockhash()) });
+};
+
+export const newUser = async () => {
+  const user = Keypair.generate();
+  await airdrop(user.publicKey);
+  return user;
+};
+
+export const tokenBalance = async (account: PublicKey, tokenProgram = TOKEN_PROGRAM_ID) =>
+  Number((await getAccount(connection, account, undefined, tokenProgram)).amount);
+
+/** Asserts the promise fails with the named program error */
+export const expectError = async (promise: Promise<unknown>, code: string) => {
+  try {
+    await promise;
+  } catch (error: any) {
+    const actual = error?.error?.errorCode?.code ?? anchor.AnchorError.parse(error?.logs)?.error.errorCode.code;
+    expect(actual ?? String(error)).to.contain(code);
+    return;
+  }
+  expect.fail(`expected ${code}`);
+};
+
+export const newMint = async (decimals = 6, tokenProgram = TOKEN_PROGRAM_ID) =>
+  createMint(connection, wallet, wallet.publicKey, null, decimals, Keypair.generate(), undefined, tokenProgram);
+
+/** A fresh token account of `owner` holding `amount` of `mint` */
+export const fundedAccount = async (mint: PublicKey, owner: Keypair | PublicKey, amount: number, tokenProgram = TOKEN_PROGRAM_ID) => {
+  const ownerKey = owner instanceof Keypair ? owner.publicKey : owner;
+  const account = await createAccount(connection, wallet, mint, ownerKey, Keypair.generate(), undefined, tokenProgram);
+  if (amount > 0) {
+    await mintTo(connection, wallet, mint, account, wallet, amount, [], undefined, tokenProgram);
+  }
+  return account;
+};
+
+export const ata = (mint: PublicKey, owner: PublicKey, tokenProgram = TOKEN_PROGRAM_ID) =>
+  getAssociatedTokenAddressSync(mint, owner, true, tokenProgram);
+
+export const ensureProgramAdmin = async () => {
+  const proof = pda(Buffer.from("program_admin"), wallet.publicKey.toBuffer());
+  if ((await connection.getAccountInfo(proof)) === null) {
+    await program.methods.addProgramAdmin().accountsPartial({ signer: wallet.publicKey, programAdmin: wallet.publicKey }).rpc();
+  }
+};
+
+export type HouseSetup = {
+  house: PublicKey;
+  houseVault: PublicKey;
+  currencyMint: PublicKey;
+  oracle: Keypair;
+};
+
+export const houseConfig = (oracle: PublicKey, overrides: Partial<Record<string, any>> = {}) => ({
+  oracleKey: oracle,
+  campaignCreationFee: new BN(0),
+  campaignManagerDiscount: new BN(0),
+  claimFee: new BN(0),
+  rewardsTax: new BN(0),
+  ...overrides,
+});
+
+/** A house administered by the provider wallet, whose campaigns it creates without fees */
+export const createHouse = async (
+  opts: {
+    currencyMint?: PublicKey;
+    playerEnergy?: any;
+    managerCollection?: PublicKey;
+    verification?: any;
+    config?: Partial<Record<string, any>>;
+  } = {}
+): Promise<HouseSetup> => {
+  await ensureProgramAdmin();
+  const name = uniqueName("house");
+  const house = housePda(name);
+  const currencyMint = opts.currencyMint ?? (await newMint());
+  const oracle = Keypair.generate();
+  await program.methods
+    .createHouse(
+      opts.managerCollection ?? null,
+      houseConfig(oracle.publicKey, opts.config),
+      name,
+      null,
+      opts.playerEnergy ?? null,
+      opts.verification ?? { either: {} }
+    )
+    .accountsPartial({
+      programAdmin: wallet.publicKey,
+      house,
+      houseAdmin: wallet.publicKey,
+      houseCurrencyMint: currencyMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .rpc();
+  return { house, houseVault: houseVaultPda(house), currencyMint, oracle };
+};
+
+export type CampaignOpts = {
+  rewardMint?: PublicKey | "native";
+  fund?: number;
+  maxRewardsPerGame?: number;
+  durationSeconds?: number;
+  nftConfig?: any;
+  tokenConfig?: any;
+  gameMint?: PublicKey;
+  burnRemainder?: boolean;
+  gameModes?: any[];
+  collections?: any[];
+  traitMultipliers?: any[];
+  stakeTiers?: any[];
+  tokenProgram?: PublicKey;
+  remainingAccounts?: any[];
+  /** creates the campaign as someone other than the house admin */
+  signer?: Keypair;
+  managerAccounts?: Record<string, PublicKey | null>;
+};
+
+export type CampaignSetup = {
+  campaign: PublicKey;
+  campaignAuth: PublicKey;
+  rewardMint: PublicKey | null;
+  rewardVault: PublicKey | null;
+  gameMint: PublicKey | null;
+  gameDepositVault: PublicKey | null;
+  endTime: number;
+};
+
+export const nftConfig = (collection: PublicKey, overrides: Partial<Record<string, any>> = {}) => ({
+  collection,
+  maxPlayerEnergy: 3,
+  energyRechargeMinutes: null,
+  energyReset: null,
+  useHouseEnergy: false,
+  maxEnergyTransferPerDay: 0,
+  tokenizedEnergy: false,
+  lockPlayerNft: false,
+  maxGameSeconds: null,
+  allowCompressedNfts: false,
+  allowToken2022Nfts: false,
+  collectionVerification: { either: {} },
+  traitRoot: null,
+  ...overrides,
+});
+
+export const tokenConfig = (spendingMint: PublicKey, energyPrice: number, tokenUse: any, overrides: Partial<Record<string, any>> = {}) => ({
+  spendingMint,
+  energyPrice: new BN(energyPrice),
+  spendingMintDecimals: spendingMint.equals(SystemProgram.programId) ? 9 : 6,
+  tokenUse,
+  earlyUnstake: null,
+  stakeYield: null,
+  slashDisputeSeconds: null,
+  revenueSplit: null,
+  priceCurve: null,
+  ...overrides,
+});
+
+export const gameMode = (energyCost: number, energyPrice: number, maxRewardsPerGame: number, oracle: PublicKey | null = null) => ({
+  energyCost,
+  energyPrice: new BN(energyPrice),
+  maxRewardsPerGame: new BN(maxRewardsPerGame),
+  oracle,
+});
+
+export const createCampaign = async (setup: HouseSetup, opts: CampaignOpts = {}): Promise<CampaignSetup> => {
+  const campaign = Keypair.generate();
+  const tokenProgram = opts.tokenProgram ?? TOKEN_PROGRAM_ID;
+  const start = await now();
+  const endTime = start + (opts.durationSeconds ?? 3600);
+  const native = opts.rewardMint === "native";
+  const rewardMint = native ? null : (opts.rewardMint as PublicKey | undefined) ?? (await newMint(6, tokenProgram));
+  const fund = opts.fund ?? 1_000_000;
+  const signer = opts.signer ?? wallet;
+  const depositor = rewardMint ? await fundedAccount(rewardMint, signer, fund, tokenProgram) : null;
+  const gameMint = opts.gameMint ?? null;
+  const nativeDeposits = opts.tokenConfig?.spendingMint?.equals?.(SystemProgram.programId) ?? false;
+  const hasDepositVault = gameMint !== null && !("burn" in (opts.tokenConfig?.tokenUse ?? {}));
+  await program.methods
+    .createCampaign(
+      uniqueName("campaign"),
+      null,
+      new BN(fund),
+      new BN(opts.maxRewardsPerGame ?? 100),
+      new BN(0),
+      { startTime: new BN(start - 10), endTime: new BN(endTime) },
+      opts.nftConfig ?? null,
+      opts.tokenConfig ?? null,
+      opts.burnRemainder ?? false,
+      opts.gameModes ?? [],
+      opts.collections ?? [],
+      opts.traitMultipliers ?? [],
+      opts.stakeTiers ?? []
+    )
+    .accountsPartial({
+      signer: signer.publicKey,
+      campaign: campaign.publicKey,
+      house: setup.house,
+      creationFeeAccount: null,
+      rewardMint,
+      houseVault: setup.houseVault,
+      houseCurrencyMint: null,
+      houseTokenProgram: null,
+      rewardDepositorAccount: depositor,
+      rewardVault: rewardMint ? rewardVaultPda(campaign.publicKey) : null,
+      solRewardVault: native ? solRewardsPda(campaign.publicKey) : null,
+      gameMint,
+      gameDepositVault: hasDepositVault ? depositVaultPda(campaign.publicKey) : null,
+      solDepositVault: nativeDeposits ? solDepositPda(campaign.publicKey) : null,
+      energyMint: opts.nftConfig?.tokenizedEnergy ? energyMintPda(campaign.publicKey) : null,
+      tokenProgram,
+      managerNftTokenAccount: null,
+      managerNftMetadata: null,
+      managerSlot: null,
+      managerCoreNft: null,
+      managerCoreCollection: null,
+      managerMemberMint: null,
+      ...opts.managerAccounts,
+    })
+    .remainingAccounts(opts.remainingAccounts ?? [])
+    .signers(signer === wallet ? [campaign] : [campaign, signer])
+    .rpc();
+  return {
+    campaign: campaign.publicKey,
+    campaignAuth: campaignAuthPda(campaign.publicKey),
+    rewardMint,
+    rewardVault: rewardMint ? rewardVaultPda(campaign.publicKey) : null,
+    gameMint,
+    gameDepositVault: hasDepositVault ? depositVaultPda(campaign.publicKey) : null,
+    endTime,
+  };
+};
+
+/** Every optional account of start_game and end_game left out, tests name the ones they use */
+export const noGameAccounts = {
+  playerNftTokenAccount: null,
+  playerNftMetadata: null,
+  playerTokenRecord: null,
+  gameDepositMint: null,
+  playersDepositAccount: null,
+  gameDepositVault: null,
+  solDepositVault: null,
+  playerCoreNft: null,
+  playerCoreCollection: null,
+  playerMemberMint: null,
+  merkleTree: null,
+  campaignAuth: null,
+  playerNftMint: null,
+  playerNftEdition: null,
+  tokenMetadataProgram: null,
+  sysvarInstructions: null,
+  authorizationRulesProgram: null,
+  authorizationRules: null,
+  mplCoreProgram: null,
+  housePlayer: null,
+  energyMint: null,
+  playerEnergyAccount: null,
+  rewardVault: null,
+  houseVault: null,
+};
+
+export type GameArgs = {
+  user: Keypair;
+  identity?: PublicKey;
+  gameMode?: number;
+  maxPrice?: number;
+  compressedNft?: any;
+  traitProof?: any;
+  oracle?: Keypair;
+  accounts?: Record<string, PublicKey | null>;
+  remainingAccounts?: any[];
+  tokenProgram?: PublicKey;
+};
+
+export const startGame = (setup: HouseSetup, campaign: CampaignSetup, args: GameArgs) =>
+  program.methods
+    .startGame(args.gameMode ?? 0, args.compressedNft ?? null, args.traitProof ?? null, new BN(args.maxPrice ?? Number.MAX_SAFE_INTEGER))
+    .accountsPartial({
+      house: setup.house,
+      campaign: campaign.campaign,
+      user: args.user.publicKey,
+      tokenProgram: args.tokenProgram ?? TOKEN_PROGRAM_ID,
+      campaignPlayer: playerPda(campaign.campaign, args.identity ?? args.user.publicKey),
+      ...noGameAccounts,
+      ...args.accounts,
+    })
+    .remainingAccounts(args.remainingAccounts ?? [])
+    .signers([args.user])
+    .rpc();
+
+export const endGame = (setup: HouseSetup, campaign: CampaignSetup, args: GameArgs & { amountWon: number }) => {
+  const tokenProgram = args.tokenProgram ?? TOKEN_PROGRAM_ID;
+  const tokenRewards = campaign.rewardMint !== null;
+  return program.methods
+    .endGame(new BN(args.amountWon), args.compressedNft ?? null)
+    .accountsPartial({
+      house: setup.house,
+      campaign: campaign.campaign,
+      campaignAuth: campaign.campaignAuth,
+      campaignPlayer: playerPda(campaign.campaign, args.identity ?? args.user.publicKey),
+      playerNftTokenAccount: null,
+      playerNftMetadata: null,
+      playerCoreNft: null,
+      playerCoreCollection: null,
+      playerMemberMint: null,
+      merkleTree: null,
+      playerTokenRecord: null,
+      playerNftMint: null,
+      playerNftEdition: null,
+      tokenMetadataProgram: null,
+      sysvarInstructions: null,
+      authorizationRulesProgram: null,
+      authorizationRules: null,
+      mplCoreProgram: null,
+      rewardMint: tokenRewards ? campaign.rewardMint : null,
+      rewardVault: tokenRewards ? campaign.rewardVault : null,
+      playerRewardTokenAccount: tokenRewards ? ata(campaign.rewardMint!, args.user.publicKey, tokenProgram) : null,
+      solRewardVault: tokenRewards ? null : solRewardsPda(campaign.campaign),
+      user: args.user.publicKey,
+      oracle: (args.oracle ?? setup.oracle).publicKey,
+      tokenProgram,
+      ...args.accounts,
+    })
+    .remainingAccounts(args.remainingAccounts ?? [])
+    .signers([args.user, args.oracle ?? setup.oracle])
+    .rpc();
+};
+
+/** start_game accounts of a user paying with tokens of the campaign game mint */
+export const payingAccounts = (campaign: CampaignSetup, playersDepositAccount: PublicKey) => ({
+  gameDepositMint: campaign.gameMint,
+  playersDepositAccount,
+  gameDepositVault: campaign.gameDepositVault,
+});
+
+/** Expires a game past max_game_seconds as `payer`, `accounts` name the locked NFT of the player */
+export const expireGame = (campaign: CampaignSetup, identity: PublicKey, payer: Keypair, accounts: Record<string, PublicKey | null> = {}) =>
+  program.methods
+    .expireGame()
+    .accountsPartial({
+      campaign: campaign.campaign,
+      campaignAuth: campaign.campaignAuth,
+      campaignPlayer: playerPda(campaign.campaign, identity),
+      playerNftTokenAccount: null,
+      playerNftOwner: null,
+      playerNftMetadata: null,
+      playerTokenRecord: null,
+      playerNftMint: null,
+      playerNftEdition: null,
+      playerCoreNft: null,
+      playerCoreCollection: null,
+      tokenMetadataProgram: null,
+      sysvarInstructions: null,
+      authorizationRulesProgram: null,
+      authorizationRules: null,
+      mplCoreProgram: null,
+      tokenProgram: null,
+      payer: payer.publicKey,
+      ...accounts,
+    })
+    .signers([payer])
+    .rpc();
+
+/** Mints the accrued energy of `identity` to the energy token account of the user */
+export const claimEnergy = (setup: HouseSetup, campaign: CampaignSetup, args: GameArgs) => {
+  const energyMint = energyMintPda(campaign.campaign);
+  return program.methods
+    .claimEnergy(args.compressedNft ?? null)
+    .accountsPartial({
+      house: setup.house,
+      campaign: campaign.campaign,
+      campaignPlayer: playerPda(campaign.campaign, args.identity ?? args.user.publicKey),
+      playerNftTokenAccount: null,
+      playerNftMetadata: null,
+      playerCoreNft: null,
+      playerCoreCollection: null,
+      playerMemberMint: null,
+      merkleTree: null,
+      energyMint,
+      playerEnergyAccount: ata(energyMint, args.user.publicKey),
+      user: args.user.publicKey,
+      ...args.accounts,
+    })
+    .remainingAccounts(args.remainingAccounts ?? [])
+    .signers([args.user])
+    .rpc();
+};
+
+/** Moves energy between the players of two identities, `accounts` prove the user holds `from` */
+export const transferEnergy = (
+  campaign: CampaignSetup,
+  user: Keypair,
+  from: PublicKey,
+  to: PublicKey,
+  amount: number,
+  accounts: Record<string, PublicKey | null> = {}
+) =>
+  program.methods
+    .transferEnergy(amount)
+    .accountsPartial({
+      campaign: campaign.campaign,
+      sourcePlayer: playerPda(campaign.campaign, from),
+      destinationPlayer: playerPda(campaign.campaign, to),
+      playerNftTokenAccount: null,
+      playerCoreNft: null,
+      user: user.publicKey,
+      ...accounts,
+    })
+    .signers([user])
+    .rpc();
+
+/** Returns the stake of `user` once the campaign ended */
+export const claimStake = (campaign: CampaignSetup, user: Keypair, accounts: Record<string, PublicKey | null> = {}) => {
+  // campaigns priced in lamports have no game mint
+  const native = campaign.gameMint === null;
+  return program.methods
+    .claimStake()
+    .accountsPartial({
+      campaignPlayer: playerPda(campaign.campaign, user.publicKey),
+      user: user.publicKey,
+      campaign: campaign.campaign,
+      campaignAuth: campaign.campaignAuth,
+      gameDepositVault: campaign.gameDepositVault,
+      stakeRecipientAccount: native ? null : ata(campaign.gameMint!, user.publicKey),
+      gameDepositMint: campaign.gameMint,
+      tokenProgram: native ? null : TOKEN_PROGRAM_ID,
+      solDepositVault: native ? solDepositPda(campaign.campaign) : null,
+      rewardVault: null,
+      rewardMint: null,
+      yieldRecipientAccount: null,
+      rewardTokenProgram: null,
+      ...accounts,
+    })
+    .signers([user])
+    .rpc();
+};
+
+/** Pays out the stake yield `user` earned so far */
+export const claimStakeYield = (campaign: CampaignSetup, user: Keypair) =>
+  program.methods
+    .claimStakeYield()
+    .accountsPartial({
+      campaign: campaign.campaign,
+      campaignPlayer: playerPda(campaign.campaign, user.publicKey),
+      user: user.publicKey,
+      campaignAuth: campaign.campaignAuth,
+      rewardMint: campaign.rewardMint,
+      rewardVault: campaign.rewardVault,
+      yieldRecipientAccount: ata(campaign.rewardMint!, user.publicKey),
+      rewardTokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([user])
+    .rpc();
+
+/** Returns `amount` of the stake of `user` before the campaign ended, minus the early unstake penalty */
+export const unstakeEarly = (campaign: CampaignSetup, user: Keypair, amount: number, accounts: Record<string, PublicKey | null> = {}) =>
+  program.methods
+    .unstakeEarly(new BN(amount))
+    .accountsPartial({
+      campaign: campaign.campaign,
+      campaignPlayer: playerPda(campaign.campaign, user.publicKey),
+      user: user.publicKey,
+      campaignAuth: campaign.campaignAuth,
+      gameDepositVault: campaign.gameDepositVault,
+      stakeRecipientAccount: ata(campaign.gameMint!, user.publicKey),
+      gameDepositMint: campaign.gameMint,
+      rewardVault: null,
+      creatorTokenAccount: null,
+      ...accounts,
+    })
+    .signers([user])
+    .rpc();
+
+/** Adds `amount` from `playersDepositAccount` to the StakeForEnergy stake of `user` */
+export const stakeTokens = (setup: HouseSetup, campaign: CampaignSetup, user: Keypair, playersDepositAccount: PublicKey, amount: number) =>
+  program.methods
+    .stakeTokens(new BN(amount))
+    .accountsPartial({
+      house: setup.house,
+      campaign: campaign.campaign,
+      campaignPlayer: playerPda(campaign.campaign, user.publicKey),
+      user: user.publicKey,
+      gameDepositMint: campaign.gameMint,
+      playersDepositAccount,
+      gameDepositVault: campaign.gameDepositVault,
+    })
+    .signers([user])
+    .rpc();
+
+/** Closes the campaign as the wallet, refunding the reward vault and draining the deposits the campaign allows */
+export const closeCampaign = (setup: HouseSetup, campaign: CampaignSetup, depositAccounts: Record<string, PublicKey | null> = {}) =>
+  program.methods
+    .closeCampaign()
+    .accountsPartial({
+      campaign: campaign.campaign,
+      campaignAuth: campaign.campaignAuth,
+      rewardWithdrawalAccount: campaign.rewardMint ? ata(campaign.rewardMint, wallet.publicKey) : null,
+      rewardMint: campaign.rewardMint,
+      rewardVault: campaign.rewardVault,
+      solRewardVault: campaign.rewardMint ? null : solRewardsPda(campaign.campaign),
+      house: setup.house,
+      creator: wallet.publicKey,
+      gameDepositVault: null,
+      depositWithdrawalAccount: null,
+      gameMint: null,
+      solDepositVault: null,
+      depositTokenProgram: null,
+      rewardTokenProgram: campaign.rewardMint ? TOKEN_PROGRAM_ID : null,
+      managerNftTokenAccount: null,
+      managerNftMetadata: null,
+      managerSlot: null,
+      managerCoreNft: null,
+      managerMemberMint: null,
+      ...depositAccounts,
+    })
+    .rpc();
+
+/** Tops up the reward pool as the wallet, from `funderTokenAccount` unless the campaign pays lamports */
+export const fundCampaign = (
+  campaign: CampaignSetup,
+  amount: number,
+  funderTokenAccount: PublicKey | null = null,
+  tokenProgram = TOKEN_PROGRAM_ID,
+  remainingAccounts: any[] = []
+) =>
+  program.methods
+    .fundCampaign(new BN(amount))
+    .accountsPartial({
+      campaign: campaign.campaign,
+      funder: wallet.publicKey,
+      rewardMint: campaign.rewardMint,
+      rewardVault: campaign.rewardVault,
+      funderTokenAccount,
+      tokenProgram: campaign.rewardMint ? tokenProgram : null,
+      solRewardVault: campaign.rewardMint ? null : solRewardsPda(campaign.campaign),
+    })
+    .remainingAccounts(remainingAccounts)
+    .rpc();
+
+/** Takes the creator share of the Pay deposits out to the game mint account of `creator` */
+export const withdrawRevenue = (campaign: CampaignSetup, creator: Keypair) =>
+  program.methods
+    .withdrawRevenue()
+    .accountsPartial({
+      campaign: campaign.campaign,
+      campaignAuth: campaign.campaignAuth,
+      creator: creator.publicKey,
+      gameDepositVault: campaign.gameDepositVault,
+      gameDepositMint: campaign.gameMint,
+      revenueWithdrawalAccount: ata(campaign.gameMint!, creator.publicKey),
+    })
+    .signers(creator === wallet ? [] : [creator])
+    .rpc();
+
+/** Opens a dispute window over `amount` of the stake of `user`, signed by the house admin or an oracle */
+export const slashStake = (
+  setup: HouseSetup,
+  campaign: CampaignSetup,
+  user: PublicKey,
+  authority: Keypair,
+  amount: number,
+  destination: any,
+  reasonCode = 7
+) =>
+  program.methods
+    .slashStake(new BN(amount), reasonCode, destination)
+    .accountsPartial({
+      house: setup.house,
+      campaign: campaign.campaign,
+      campaignPlayer: playerPda(campaign.campaign, user),
+      stakeSlash: slashPda(playerPda(campaign.campaign, user)),
+      authority: authority.publicKey,
+    })
+    .signers([authority])
+    .rpc();
+
+/** Settles the undisputed slash of `user`, a reward pool slash moves the tokens into the reward vault */
+export const finalizeSlash = (campaign: CampaignSetup, user: PublicKey, authority: PublicKey, accounts: Record<string, PublicKey | null> = {}) =>
+  program.methods
+    .finalizeSlash()
+    .accountsPartial({
+      campaign: campaign.campaign,
+      stakeSlash: slashPda(playerPda(campaign.campaign, user)),
+      authority,
+      campaignAuth: campaign.campaignAuth,
+      gameDepositVault: campaign.gameDepositVault,
+      gameDepositMint: campaign.gameMint,
+      rewardVault: campaign.rewardVault,
+      ...accounts,
+    })
+    .rpc();
+
+/** Overturns the slash of `user` as the wallet, the house admin */
+export const restoreStake = (setup: HouseSetup, campaign: CampaignSetup, user: PublicKey, authority: PublicKey) =>
+  program.methods
+    .restoreStake()
+    .accountsPartial({
+      house: setup.house,
+      campaign: campaign.campaign,
+      campaignPlayer: playerPda(campaign.campaign, user),
+      stakeSlash: slashPda(playerPda(campaign.campaign, user)),
+      authority,
+      houseAdmin: wallet.publicKey,
+      campaignAuth: campaign.campaignAuth,
+      gameDepositVault: null,
+      gameDepositMint: null,
+      playerTokenAccount: null,
+      tokenProgram: null,
+    })
+    .rpc();
+
+/** Rewrites a House, Campaign or CampaignPlayer in the current layout, players need their migrated campaign */
+export const migrateAccount = (legacyAccount: PublicKey, campaign: PublicKey | null = null, gameDepositVault: PublicKey | null = null) =>
+  program.methods
+    .migrateAccount()
+    .accountsPartial({ legacyAccount, campaign, gameDepositVault, payer: wallet.publicKey })
+    .rpc();
+
+export const createCoreCollection = async (plugins: any[] = []) => {
+  const collection = generateSigner(umi);
+  await createCollection(umi, { collection, name: "collection", uri: "", plugins }).sendAndConfirm(umi);
+  return toWeb3JsPublicKey(collection.publicKey);
+};
+
+/** A Core asset of `owner` whose update authority is `collection` */
+export const createCoreAsset = async (collection: PublicKey, owner: PublicKey, plugins: any[] = []) => {
+  const asset = generateSigner(umi);
+  await create(umi, {
+    asset,
+    collection: await fetchCollection(umi, fromWeb3JsPublicKey(collection)),
+    owner: fromWeb3JsPublicKey(owner),
+    name: "player",
+    uri: "",
+    plugins,
+  }).sendAndConfirm(umi);
+  return toWeb3JsPublicKey(asset.publicKey);
+};
+
+export const coreAccounts = (asset: PublicKey, collection: PublicKey) => ({
+  playerCoreNft: asset,
+  playerCoreCollection: collection,
+});
+
+/** A 0 decimals Token-2022 mint of the wallet whose `pointer` extension points at the mint itself */
+const newPointerMint = async (extensionType: ExtensionType, pointer: (mint: PublicKey) => TransactionInstruction) => {
+  const mint = Keypair.generate();
+  const space = getMintLen([extensionType]);
+  const transaction = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: wallet.publicKey,
+      newAccountPubkey: mint.publicKey,
+      space,
+      lamports: await connection.getMinimumBalanceForRentExemption(space),
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    pointer(mint.publicKey),
+    createInitializeMintInstruction(mint.publicKey, 0, wallet.publicKey, null, TOKEN_2022_PROGRAM_ID)
+  );
+  await sendAndConfirmTransaction(connection, transaction, [wallet, mint]);
+  return mint.publicKey;
+};
+
+/** A Token-2022 collection built on the token-group extension, updated by the wallet */
+export const createToken2022Group = async () => {
+  const group = await newPointerMint(ExtensionType.GroupPointer, (mint) =>
+    createInitializeGroupPointerInstruction(mint, wallet.publicKey, mint, TOKEN_2022_PROGRAM_ID)
+  );
+  await tokenGroupInitializeGroupWithRentTransfer(connection, wallet, group, wallet.publicKey, wallet.publicKey, BigInt(100), [], undefined, TOKEN_2022_PROGRAM_ID);
+  return group;
+};
+
+/** A Token-2022 NFT of `group` held by `owner`, with the token account start_game checks */
+export const createToken2022Member = async (group: PublicKey, owner: PublicKey) => {
+  const mint = await newPointerMint(ExtensionType.GroupMemberPointer, (mint) =>
+    createInitializeGroupMemberPointerInstruction(mint, wallet.publicKey, mint, TOKEN_2022_PROGRAM_ID)
+  );
+  await tokenGroupMemberInitializeWithRentTransfer(connection, wallet, mint, wallet.publicKey, group, wallet.publicKey, [], undefined, TOKEN_2022_PROGRAM_ID);
+  const tokenAccount = await fundedAccount(mint, owner, 1, TOKEN_2022_PROGRAM_ID);
+  return { mint, tokenAccount };
+};
+
+/** A Token Metadata collection NFT of the provider wallet */
+export const createMetadataCollection = async () => {
+  const mint = generateSigner(umi);
+  await createNft(umi, { mint, name: "collection", uri: "", sellerFeeBasisPoints: percentAmount(0), isCollection: true }).sendAndConfirm(umi);
+  return toWeb3JsPublicKey(mint.publicKey);
+};
+
+/** A Token Metadata NFT of `owner`, a verified member of `collection` when given */
+export const createMetadataNft = async (owner: PublicKey, opts: { programmable?: boolean; collection?: PublicKey } = {}) => {
+  const mint = generateSigner(umi);
+  const args = {
+    mint,
+    name: "player",
+    uri: "",
+    sellerFeeBasisPoints: percentAmount(0),
+    tokenOwner: fromWeb3JsPublicKey(owner),
+    collection: opts.collection ? some({ key: fromWeb3JsPublicKey(opts.collection), verified: false }) : undefined,
+  };
+  await (opts.programmable ? createProgrammableNft(umi, args) : createNft(umi, args)).sendAndConfirm(umi);
+  if (opts.collection) {
+    await verifyCollectionV1(umi, {
+      metadata: findMetadataPda(umi, { mint: mint.publicKey }),
+      collectionMint: fromWeb3JsPublicKey(opts.collection),
+    }).sendAndConfirm(umi);
+  }
+  const tokenAccount = ata(toWeb3JsPublicKey(mint.publicKey), owner);
+  return {
+    mint: toWeb3JsPublicKey(mint.publicKey),
+    metadata: toWeb3JsPublicKey(findMetadataPda(umi, { mint: mint.publicKey })[0]),
+    edition: toWeb3JsPublicKey(findMasterEditionPda(umi, { mint: mint.publicKey })[0]),
+    tokenAccount,
+    tokenRecord: opts.programmable
+      ? toWeb3JsPublicKey(findTokenRecordPda(umi, { mint: mint.publicKey, token: fromWeb3JsPublicKey(tokenAccount) })[0])
+      : null,
+    tokenStandard: opts.programmable ? TokenStandard.ProgrammableNonFungible : TokenStandard.NonFungible,
+  };
+};
+
+export type MetadataNft = Awaited<ReturnType<typeof createMetadataNft>>;
+
+/** start_game and end_game accounts of a player presenting a Token Metadata NFT */
+export const metadataAccounts = (nft: MetadataNft) => ({
+  playerNftTokenAccount: nft.tokenAccount,
+  playerNftMetadata: nft.metadata,
+  playerTokenRecord: nft.tokenRecord,
+});
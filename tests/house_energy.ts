@@ -0,0 +1,60 @@
+import { expect } from "chai";
+import {
+  coreAccounts,
+  createCampaign,
+  createCoreAsset,
+  createCoreCollection,
+  createHouse,
+  endGame,
+  expectError,
+  housePlayerPda,
+  newUser,
+  nftConfig,
+  program,
+  startGame,
+} from "./helpers";
+
+describe("house energy", () => {
+  it("shares one energy pool between the campaigns of a house", async () => {
+    const setup = await createHouse({
+      playerEnergy: { maxPlayerEnergy: 1, energyRechargeMinutes: null, energyReset: null },
+    });
+    const collection = await createCoreCollection();
+    const config = nftConfig(collection, { useHouseEnergy: true });
+    const first = await createCampaign(setup, { nftConfig: config });
+    const second = await createCampaign(setup, { nftConfig: config });
+    const user = await newUser();
+    const asset = await createCoreAsset(collection, user.publicKey);
+    const housePlayer = housePlayerPda(setup.house, asset);
+    const game = { user, identity: asset, accounts: { ...coreAccounts(asset, collection), housePlayer } };
+
+    await startGame(setup, first, game);
+    await endGame(setup, first, { user, identity: asset, accounts: coreAccounts(asset, collection), amountWon: 0 });
+    expect((await program.account.housePlayer.fetch(housePlayer)).energy).to.equal(0);
+
+    // the second campaign draws from the same, now empty, pool
+    await expectError(startGame(setup, second, game), "OutOfEnergy");
+  });
+
+  it("rejects a house the campaign does not belong to", async () => {
+    const setup = await createHouse({
+      playerEnergy: { maxPlayerEnergy: 1, energyRechargeMinutes: null, energyReset: null },
+    });
+    const other = await createHouse({
+      playerEnergy: { maxPlayerEnergy: 5, energyRechargeMinutes: null, energyReset: null },
+    });
+    const collection = await createCoreCollection();
+    const campaign = await createCampaign(setup, { nftConfig: nftConfig(collection, { useHouseEnergy: true }) });
+    const user = await newUser();
+    const asset = await createCoreAsset(collection, user.publicKey);
+
+    await expectError(
+      startGame(other, campaign, {
+        user,
+        identity: asset,
+        accounts: { ...coreAccounts(asset, collection), housePlayer: housePlayerPda(other.house, asset) },
+      }),
+      "InvalidInput"
+    );
+  });
+});
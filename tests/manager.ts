@@ -0,0 +1,50 @@
+import { BN } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import {
+  createCampaign,
+  createCoreAsset,
+  createCoreCollection,
+  createHouse,
+  expectError,
+  managerSlotPda,
+  newUser,
+  nftConfig,
+  program,
+} from "./helpers";
+
+describe("manager passes", () => {
+  it("lets Core pass holders create campaigns without the creation fee", async () => {
+    const managerCollection = await createCoreCollection();
+    const setup = await createHouse({ managerCollection, config: { campaignCreationFee: new BN(1_000) } });
+    const manager = await newUser();
+    const pass = await createCoreAsset(managerCollection, manager.publicKey);
+    const managerAccounts = {
+      managerSlot: managerSlotPda(setup.house, pass),
+      managerCoreNft: pass,
+      managerCoreCollection: managerCollection,
+    };
+
+    // without a pass the creation fee account is required
+    await expectError(createCampaign(setup, { signer: manager, nftConfig: nftConfig(managerCollection) }), "InvalidInput");
+
+    const { campaign } = await createCampaign(setup, { signer: manager, managerAccounts, nftConfig: nftConfig(managerCollection) });
+    const state = await program.account.campaign.fetch(campaign);
+    expect(state.managerIdentity.identityType).to.deep.equal({ mplCore: {} });
+    expect(state.managerIdentity.pubkey.toBase58()).to.equal(pass.toBase58());
+    const slot = await program.account.managerSlot.fetch(managerAccounts.managerSlot);
+    expect(slot.manager.toBase58()).to.equal(pass.toBase58());
+    expect(slot.campaign.toBase58()).to.equal(campaign.toBase58());
+
+    // a pass held by someone else is refused
+    const other = await newUser();
+    const otherPass = await createCoreAsset(managerCollection, other.publicKey);
+    await expectError(
+      createCampaign(setup, {
+        signer: manager,
+        managerAccounts: { managerSlot: managerSlotPda(setup.house, otherPass), managerCoreNft: otherPass, managerCoreCollection: managerCollection },
+        nftConfig: nftConfig(managerCollection),
+      }),
+      "TokenOwnerMismatch"
+    );
+  });
+});
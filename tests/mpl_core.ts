@@ -0,0 +1,68 @@
+import { Keypair } from "@solana/web3.js";
+import { fromWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { expect } from "chai";
+import {
+  coreAccounts,
+  createCampaign,
+  createCoreAsset,
+  createCoreCollection,
+  createHouse,
+  endGame,
+  expectError,
+  newUser,
+  nftConfig,
+  playerPda,
+  program,
+  startGame,
+} from "./helpers";
+
+describe("mpl core", () => {
+  it("requires the asset collection and honors its freeze plugins", async () => {
+    const setup = await createHouse();
+    const collection = await createCoreCollection();
+    const frozenCollection = await createCoreCollection([{ type: "PermanentFreezeDelegate", frozen: true }]);
+    const campaign = await createCampaign(setup, {
+      nftConfig: nftConfig(collection),
+      collections: [{ collection: frozenCollection, maxPlayerEnergy: 3, energyRechargeMinutes: null, rewardMultiplierBps: 10_000 }],
+    });
+    const user = await newUser();
+
+    const asset = await createCoreAsset(collection, user.publicKey);
+    const game = { user, identity: asset, accounts: coreAccounts(asset, collection) };
+    // leaving the collection out would hide its plugins
+    await expectError(startGame(setup, campaign, { ...game, accounts: { playerCoreNft: asset } }), "CoreCollectionRequired");
+    const otherCollection = await createCoreCollection();
+    await expectError(startGame(setup, campaign, { ...game, accounts: coreAccounts(asset, otherCollection) }), "CollectionKeyMismatch");
+
+    const frozenAsset = await createCoreAsset(collection, user.publicKey, [{ type: "FreezeDelegate", frozen: true }]);
+    await expectError(
+      startGame(setup, campaign, { user, identity: frozenAsset, accounts: coreAccounts(frozenAsset, collection) }),
+      "AssetFrozen"
+    );
+    const assetOfFrozenCollection = await createCoreAsset(frozenCollection, user.publicKey);
+    await expectError(
+      startGame(setup, campaign, { user, identity: assetOfFrozenCollection, accounts: coreAccounts(assetOfFrozenCollection, frozenCollection) }),
+      "AssetFrozen"
+    );
+
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { ...game, amountWon: 1 });
+    const player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, asset));
+    expect(player.playerIdentity.identityType).to.deep.equal({ mplCore: {} });
+  });
+
+  it("accepts collections that delegate updates to the campaign collection", async () => {
+    const setup = await createHouse();
+    const authority = Keypair.generate();
+    const collection = await createCoreCollection([
+      { type: "UpdateDelegate", authority: { type: "Address", address: fromWeb3JsPublicKey(authority.publicKey) }, additionalDelegates: [] },
+    ]);
+    const campaign = await createCampaign(setup, { nftConfig: nftConfig(authority.publicKey) });
+    const user = await newUser();
+    const asset = await createCoreAsset(collection, user.publicKey);
+    const game = { user, identity: asset, accounts: coreAccounts(asset, collection) };
+
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { ...game, amountWon: 1 });
+  });
+});
@@ -0,0 +1,61 @@
+import { expect } from "chai";
+import {
+  coreAccounts,
+  createCampaign,
+  createCoreAsset,
+  createCoreCollection,
+  createHouse,
+  endGame,
+  expectError,
+  newUser,
+  nftConfig,
+  playerPda,
+  program,
+  startGame,
+} from "./helpers";
+
+describe("multi collection", () => {
+  it("applies the energy and reward multiplier of the collection the NFT belongs to", async () => {
+    const setup = await createHouse();
+    const partner = await createCoreCollection();
+    const premium = await createCoreCollection();
+    const campaign = await createCampaign(setup, {
+      nftConfig: nftConfig(partner),
+      maxRewardsPerGame: 100,
+      collections: [
+        { collection: partner, maxPlayerEnergy: 1, energyRechargeMinutes: null, rewardMultiplierBps: 10_000 },
+        { collection: premium, maxPlayerEnergy: 2, energyRechargeMinutes: null, rewardMultiplierBps: 20_000 },
+      ],
+    });
+    const user = await newUser();
+
+    const premiumAsset = await createCoreAsset(premium, user.publicKey);
+    const premiumGame = { user, identity: premiumAsset, accounts: coreAccounts(premiumAsset, premium) };
+    await startGame(setup, campaign, premiumGame);
+    let player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, premiumAsset));
+    expect(player.collectionIndex).to.equal(1);
+    expect(player.energy).to.equal(1);
+    expect(player.gameMaxRewards.toNumber()).to.equal(200);
+    expect((await program.account.campaign.fetch(campaign.campaign)).reservedRewards.toNumber()).to.equal(200);
+    await expectError(endGame(setup, campaign, { ...premiumGame, amountWon: 201 }), "AmountTooHigh");
+    await endGame(setup, campaign, { ...premiumGame, amountWon: 150 });
+    player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, premiumAsset));
+    expect(player.rewardsClaimed.toNumber()).to.equal(150);
+
+    const partnerAsset = await createCoreAsset(partner, user.publicKey);
+    const partnerGame = { user, identity: partnerAsset, accounts: coreAccounts(partnerAsset, partner) };
+    await startGame(setup, campaign, partnerGame);
+    player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, partnerAsset));
+    expect(player.collectionIndex).to.equal(0);
+    expect(player.gameMaxRewards.toNumber()).to.equal(100);
+    await endGame(setup, campaign, { ...partnerGame, amountWon: 0 });
+    await expectError(startGame(setup, campaign, partnerGame), "OutOfEnergy");
+
+    const strangers = await createCoreCollection();
+    const strangerAsset = await createCoreAsset(strangers, user.publicKey);
+    await expectError(
+      startGame(setup, campaign, { user, identity: strangerAsset, accounts: coreAccounts(strangerAsset, strangers) }),
+      "CollectionKeyMismatch"
+    );
+  });
+});
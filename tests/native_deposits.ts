@@ -0,0 +1,69 @@
+import { LAMPORTS_PER_SOL, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  claimStake,
+  closeCampaign,
+  connection,
+  createCampaign,
+  createHouse,
+  endGame,
+  expectError,
+  newUser,
+  program,
+  solDepositPda,
+  startGame,
+  tokenConfig,
+  waitUntil,
+} from "./helpers";
+
+describe("native sol deposits", () => {
+  const price = LAMPORTS_PER_SOL / 10;
+
+  it("charges Pay games in lamports and hands them to the creator on close", async () => {
+    const setup = await createHouse();
+    const campaign = await createCampaign(setup, { tokenConfig: tokenConfig(SystemProgram.programId, price, { pay: {} }) });
+    const solDepositVault = solDepositPda(campaign.campaign);
+    const rent = await connection.getBalance(solDepositVault);
+    expect(rent).to.be.greaterThan(0);
+    const user = await newUser();
+    const game = { user, accounts: { solDepositVault } };
+
+    await expectError(startGame(setup, campaign, { user }), "InvalidInput");
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { user, amountWon: 0 });
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { user, amountWon: 0 });
+    expect(await connection.getBalance(solDepositVault)).to.equal(rent + 2 * price);
+
+    await closeCampaign(setup, campaign, { solDepositVault });
+    expect(await connection.getBalance(solDepositVault)).to.equal(0);
+  });
+
+  it("escrows lamport stakes past the close and returns them to the staker", async () => {
+    const setup = await createHouse();
+    const campaign = await createCampaign(setup, {
+      tokenConfig: tokenConfig(SystemProgram.programId, price, { stake: {} }),
+      durationSeconds: 8,
+    });
+    const solDepositVault = solDepositPda(campaign.campaign);
+    const rent = await connection.getBalance(solDepositVault);
+    const user = await newUser();
+
+    await startGame(setup, campaign, { user, accounts: { solDepositVault } });
+    await endGame(setup, campaign, { user, amountWon: 0 });
+    const state = await program.account.campaign.fetch(campaign.campaign);
+    expect(state.totalStaked.toNumber()).to.equal(price);
+    expect(await connection.getBalance(solDepositVault)).to.equal(rent + price);
+
+    await expectError(claimStake(campaign, user), "ActiveCampaign");
+    await waitUntil(campaign.endTime);
+    await closeCampaign(setup, campaign, { solDepositVault });
+    expect(await connection.getBalance(solDepositVault)).to.equal(rent + price);
+
+    const before = await connection.getBalance(user.publicKey);
+    await claimStake(campaign, user);
+    expect(await connection.getBalance(solDepositVault)).to.equal(rent);
+    // less the transaction fee
+    expect(await connection.getBalance(user.publicKey)).to.be.greaterThan(before + price - LAMPORTS_PER_SOL / 1_000);
+  });
+});
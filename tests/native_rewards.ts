@@ -0,0 +1,45 @@
+import { expect } from "chai";
+import {
+  closeCampaign,
+  connection,
+  createCampaign,
+  createHouse,
+  endGame,
+  expectError,
+  fundCampaign,
+  newUser,
+  program,
+  solRewardsPda,
+  startGame,
+} from "./helpers";
+
+describe("native sol rewards", () => {
+  it("pays winnings in lamports from a campaign PDA that can be topped up and refunded", async () => {
+    const setup = await createHouse();
+    await expectError(createCampaign(setup, { rewardMint: "native", burnRemainder: true }), "InvalidInput");
+
+    const campaign = await createCampaign(setup, { rewardMint: "native", fund: 1_000_000, maxRewardsPerGame: 100_000 });
+    const solRewardVault = solRewardsPda(campaign.campaign);
+    expect(campaign.rewardMint).to.equal(null);
+    const funded = await connection.getBalance(solRewardVault);
+    const state = await program.account.campaign.fetch(campaign.campaign);
+    expect(state.rewardsAvailable.toNumber()).to.equal(1_000_000);
+    // rent exemption comes on top of the funding
+    expect(funded).to.be.greaterThan(1_000_000);
+
+    const user = await newUser();
+    await startGame(setup, campaign, { user });
+    const before = await connection.getBalance(user.publicKey);
+    await endGame(setup, campaign, { user, amountWon: 60_000 });
+    expect(await connection.getBalance(user.publicKey)).to.equal(before + 60_000);
+    expect(await connection.getBalance(solRewardVault)).to.equal(funded - 60_000);
+
+    await fundCampaign(campaign, 500_000);
+    expect(await connection.getBalance(solRewardVault)).to.equal(funded - 60_000 + 500_000);
+    const topped = await program.account.campaign.fetch(campaign.campaign);
+    expect(topped.rewardsAvailable.toNumber()).to.equal(1_000_000 - 60_000 + 500_000);
+
+    await closeCampaign(setup, campaign);
+    expect(await connection.getBalance(solRewardVault)).to.equal(0);
+  });
+});
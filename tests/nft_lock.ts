@@ -0,0 +1,70 @@
+import { MPL_CORE_PROGRAM_ID, fetchAsset } from "@metaplex-foundation/mpl-core";
+import { fromWeb3JsPublicKey, toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { expect } from "chai";
+import {
+  coreAccounts,
+  createCampaign,
+  createCoreAsset,
+  createCoreCollection,
+  createHouse,
+  endGame,
+  expectError,
+  expireGame,
+  newUser,
+  nftConfig,
+  playerPda,
+  program,
+  startGame,
+  umi,
+  waitUntil,
+} from "./helpers";
+
+describe("nft lock", () => {
+  const mplCoreProgram = toWeb3JsPublicKey(MPL_CORE_PROGRAM_ID);
+
+  it("lets anyone release a locked NFT once the game outlived max_game_seconds", async () => {
+    const setup = await createHouse();
+    const collection = await createCoreCollection();
+    const campaign = await createCampaign(setup, { nftConfig: nftConfig(collection, { lockPlayerNft: true, maxGameSeconds: 2 }) });
+    const user = await newUser();
+    const asset = await createCoreAsset(collection, user.publicKey);
+    const lockAccounts = { ...coreAccounts(asset, collection), mplCoreProgram };
+    const game = { user, identity: asset, accounts: { ...lockAccounts, campaignAuth: campaign.campaignAuth } };
+
+    await startGame(setup, campaign, game);
+    expect((await fetchAsset(umi, fromWeb3JsPublicKey(asset))).freezeDelegate?.frozen).to.equal(true);
+    const stranger = await newUser();
+    await expectError(expireGame(campaign, asset, stranger, lockAccounts), "GameNotExpired");
+
+    const { gameStartTime } = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, asset));
+    await waitUntil(gameStartTime.toNumber() + 2);
+    // the frozen asset must be released along with the game
+    await expectError(expireGame(campaign, asset, stranger), "NftLockAccountsRequired");
+    await expireGame(campaign, asset, stranger, lockAccounts);
+    expect((await fetchAsset(umi, fromWeb3JsPublicKey(asset))).freezeDelegate?.frozen).to.equal(false);
+    const player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, asset));
+    expect(player.inGame).to.equal(false);
+    const state = await program.account.campaign.fetch(campaign.campaign);
+    expect(state.activeGames).to.equal(0);
+    expect(state.reservedRewards.toNumber()).to.equal(0);
+    await expectError(expireGame(campaign, asset, stranger, lockAccounts), "InvalidInput");
+
+    // the kept plugin is frozen again by the campaign and removed by end_game
+    await startGame(setup, campaign, game);
+    expect((await fetchAsset(umi, fromWeb3JsPublicKey(asset))).freezeDelegate?.frozen).to.equal(true);
+    await endGame(setup, campaign, { ...game, amountWon: 0 });
+    expect((await fetchAsset(umi, fromWeb3JsPublicKey(asset))).freezeDelegate).to.equal(undefined);
+  });
+
+  it("refuses to lock identities that cannot be frozen", async () => {
+    const setup = await createHouse();
+    const collection = await createCoreCollection();
+    for (const allowed of [{ allowCompressedNfts: true }, { allowToken2022Nfts: true }]) {
+      await expectError(
+        createCampaign(setup, { nftConfig: nftConfig(collection, { lockPlayerNft: true, maxGameSeconds: 60, ...allowed }) }),
+        "NftLockUnsupported"
+      );
+    }
+    await expectError(createCampaign(setup, { nftConfig: nftConfig(collection, { lockPlayerNft: true }) }), "InvalidInput");
+  });
+});
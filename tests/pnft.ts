@@ -0,0 +1,51 @@
+import { createSignerFromKeypair, generateSigner } from "@metaplex-foundation/umi";
+import { TokenStandard, delegateSaleV1, findMetadataPda } from "@metaplex-foundation/mpl-token-metadata";
+import { fromWeb3JsKeypair, fromWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters";
+import { expect } from "chai";
+import {
+  createCampaign,
+  createHouse,
+  createMetadataNft,
+  endGame,
+  expectError,
+  metadataAccounts,
+  newUser,
+  nftConfig,
+  playerPda,
+  program,
+  startGame,
+  umi,
+  wallet,
+} from "./helpers";
+
+describe("programmable nfts", () => {
+  it("plays with an unlocked pNFT and refuses one delegated to a sale", async () => {
+    const setup = await createHouse();
+    const campaign = await createCampaign(setup, {
+      nftConfig: nftConfig(wallet.publicKey, { collectionVerification: { creator: {} } }),
+    });
+    const user = await newUser();
+    const nft = await createMetadataNft(user.publicKey, { programmable: true });
+    const game = { user, identity: nft.mint, accounts: metadataAccounts(nft) };
+
+    await expectError(
+      startGame(setup, campaign, { ...game, accounts: { ...metadataAccounts(nft), playerTokenRecord: null } }),
+      "TokenRecordRequired"
+    );
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { ...game, amountWon: 2 });
+    const player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, nft.mint));
+    expect(player.gamesPlayed).to.equal(1);
+
+    // a marketplace listing hands the sale delegate over the token record
+    await delegateSaleV1(umi, {
+      mint: fromWeb3JsPublicKey(nft.mint),
+      metadata: findMetadataPda(umi, { mint: fromWeb3JsPublicKey(nft.mint) }),
+      tokenOwner: fromWeb3JsPublicKey(user.publicKey),
+      authority: createSignerFromKeypair(umi, fromWeb3JsKeypair(user)),
+      delegate: generateSigner(umi).publicKey,
+      tokenStandard: TokenStandard.ProgrammableNonFungible,
+    }).sendAndConfirm(umi);
+    await expectError(startGame(setup, campaign, game), "AssetListed");
+  });
+});
@@ -0,0 +1,52 @@
+import { expect } from "chai";
+import {
+  createCampaign,
+  createHouse,
+  endGame,
+  expectError,
+  fundedAccount,
+  newMint,
+  newUser,
+  payingAccounts,
+  startGame,
+  tokenBalance,
+  tokenConfig,
+} from "./helpers";
+
+describe("price curve", () => {
+  it("raises the price along the curve and refuses prices above the player's maximum", async () => {
+    const setup = await createHouse();
+    const gameMint = await newMint();
+
+    // every game of the day costs half the base price more than the one before
+    const daily = await createCampaign(setup, {
+      gameMint,
+      tokenConfig: tokenConfig(gameMint, 100, { pay: {} }, { priceCurve: { dailyEscalating: { stepBps: 5_000 } } }),
+    });
+    const user = await newUser();
+    const deposit = await fundedAccount(gameMint, user, 1_000);
+    const game = { user, accounts: payingAccounts(daily, deposit) };
+    await startGame(setup, daily, { ...game, maxPrice: 100 });
+    await endGame(setup, daily, { ...game, amountWon: 0 });
+    await expectError(startGame(setup, daily, { ...game, maxPrice: 149 }), "PriceAboveMax");
+    expect(await tokenBalance(deposit)).to.equal(900);
+    await startGame(setup, daily, { ...game, maxPrice: 150 });
+    await endGame(setup, daily, { ...game, amountWon: 0 });
+    expect(await tokenBalance(daily.gameDepositVault!)).to.equal(250);
+    await startGame(setup, daily, game);
+    expect(await tokenBalance(daily.gameDepositVault!)).to.equal(450);
+
+    // the campaign wide curve also prices the games of other players
+    const busy = await createCampaign(setup, {
+      gameMint,
+      tokenConfig: tokenConfig(gameMint, 100, { pay: {} }, { priceCurve: { totalGames: { games: 1, stepBps: 10_000 } } }),
+    });
+    await startGame(setup, busy, { ...game, accounts: payingAccounts(busy, deposit), maxPrice: 100 });
+    await endGame(setup, busy, { user, amountWon: 0 });
+    const other = await newUser();
+    const otherGame = { user: other, accounts: payingAccounts(busy, await fundedAccount(gameMint, other, 1_000)) };
+    await expectError(startGame(setup, busy, { ...otherGame, maxPrice: 100 }), "PriceAboveMax");
+    await startGame(setup, busy, { ...otherGame, maxPrice: 200 });
+    expect(await tokenBalance(busy.gameDepositVault!)).to.equal(300);
+  });
+});
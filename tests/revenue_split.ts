@@ -0,0 +1,57 @@
+import { getMint } from "@solana/spl-token";
+import { expect } from "chai";
+import {
+  ata,
+  connection,
+  createCampaign,
+  createHouse,
+  expectError,
+  fundedAccount,
+  newMint,
+  newUser,
+  payingAccounts,
+  program,
+  startGame,
+  tokenBalance,
+  tokenConfig,
+  wallet,
+  withdrawRevenue,
+} from "./helpers";
+
+describe("revenue split", () => {
+  it("divides Pay deposits between creator, house, reward pool and burn", async () => {
+    const gameMint = await newMint();
+    const setup = await createHouse({ currencyMint: gameMint });
+    const split = (creatorBps: number, houseBps: number | null, rewardPoolBps: number) =>
+      tokenConfig(gameMint, 1_000, { pay: {} }, { revenueSplit: { creatorBps, houseBps, rewardPoolBps } });
+
+    await expectError(createCampaign(setup, { gameMint, rewardMint: gameMint, tokenConfig: split(6_000, 3_000, 2_000) }), "TaxTooHigh");
+    const campaign = await createCampaign(setup, { gameMint, rewardMint: gameMint, tokenConfig: split(5_000, 2_000, 1_000) });
+    const user = await newUser();
+    const deposit = await fundedAccount(gameMint, user, 1_000);
+    const accounts = { ...payingAccounts(campaign, deposit), rewardVault: campaign.rewardVault, houseVault: setup.houseVault };
+
+    await expectError(startGame(setup, campaign, { user, accounts: { ...accounts, houseVault: null } }), "InvalidInput");
+    const before = await program.account.campaign.fetch(campaign.campaign);
+    const supply = Number((await getMint(connection, gameMint)).supply);
+    const houseFees = await tokenBalance(setup.houseVault);
+
+    await startGame(setup, campaign, { user, accounts });
+    expect(await tokenBalance(deposit)).to.equal(0);
+    expect(await tokenBalance(campaign.gameDepositVault!)).to.equal(500);
+    expect(await tokenBalance(setup.houseVault)).to.equal(houseFees + 200);
+    expect(Number((await getMint(connection, gameMint)).supply)).to.equal(supply - 200);
+    const after = await program.account.campaign.fetch(campaign.campaign);
+    expect(after.creatorRevenue.toNumber()).to.equal(500);
+    expect(after.rewardsAvailable.toNumber() - before.rewardsAvailable.toNumber()).to.equal(100);
+    expect((await program.account.house.fetch(setup.house)).unclaimedHouseFees.toNumber()).to.equal(200);
+
+    // the creator share can be taken out while the campaign runs
+    await expectError(withdrawRevenue(campaign, await newUser()), "ConstraintHasOne");
+    await withdrawRevenue(campaign, wallet);
+    expect(await tokenBalance(ata(gameMint, wallet.publicKey))).to.equal(500);
+    expect(await tokenBalance(campaign.gameDepositVault!)).to.equal(0);
+    expect((await program.account.campaign.fetch(campaign.campaign)).creatorRevenue.toNumber()).to.equal(0);
+    await expectError(withdrawRevenue(campaign, wallet), "InsufficientFunds");
+  });
+});
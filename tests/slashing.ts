@@ -0,0 +1,100 @@
+import { BN } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  CampaignSetup,
+  airdrop,
+  closeCampaign,
+  createCampaign,
+  createHouse,
+  endGame,
+  expectError,
+  finalizeSlash,
+  fundedAccount,
+  newMint,
+  newUser,
+  payingAccounts,
+  playerPda,
+  program,
+  restoreStake,
+  slashPda,
+  slashStake,
+  startGame,
+  tokenBalance,
+  tokenConfig,
+  waitUntil,
+} from "./helpers";
+
+describe("slashing", () => {
+  it("holds a slashed stake for the dispute window before it funds the reward pool", async () => {
+    const setup = await createHouse();
+    await airdrop(setup.oracle.publicKey);
+    const gameMint = await newMint();
+    const stake = async (campaign: CampaignSetup, user: Keypair) => {
+      const deposit = await fundedAccount(gameMint, user, 100);
+      await startGame(setup, campaign, { user, accounts: payingAccounts(campaign, deposit) });
+      await endGame(setup, campaign, { user, amountWon: 0 });
+    };
+
+    const unslashable = await createCampaign(setup, { gameMint, tokenConfig: tokenConfig(gameMint, 100, { stake: {} }) });
+    const bystander = await newUser();
+    await stake(unslashable, bystander);
+    await expectError(slashStake(setup, unslashable, bystander.publicKey, setup.oracle, 10, { burn: {} }), "SlashingDisabled");
+
+    // slashed tokens can only fund rewards paid in the staked mint
+    const otherRewards = await createCampaign(setup, {
+      gameMint,
+      tokenConfig: tokenConfig(gameMint, 100, { stake: {} }, { slashDisputeSeconds: new BN(6) }),
+    });
+    await stake(otherRewards, bystander);
+    await expectError(slashStake(setup, otherRewards, bystander.publicKey, setup.oracle, 10, { rewardPool: {} }), "InvalidInput");
+
+    const campaign = await createCampaign(setup, {
+      gameMint,
+      rewardMint: gameMint,
+      tokenConfig: tokenConfig(gameMint, 100, { stake: {} }, { slashDisputeSeconds: new BN(6) }),
+    });
+    const user = await newUser();
+    await stake(campaign, user);
+    const campaignPlayer = playerPda(campaign.campaign, user.publicKey);
+    const stakeSlash = slashPda(campaignPlayer);
+
+    await expectError(slashStake(setup, campaign, user.publicKey, await newUser(), 10, { burn: {} }), "NotClubAdmin");
+    await expectError(slashStake(setup, campaign, user.publicKey, setup.oracle, 10, { creator: {} }), "InvalidInput");
+    await expectError(slashStake(setup, campaign, user.publicKey, setup.oracle, 101, { burn: {} }), "AmountTooHigh");
+
+    // the house admin overturns a slash while the dispute window is open
+    await slashStake(setup, campaign, user.publicKey, setup.oracle, 40, { rewardPool: {} });
+    const slash = await program.account.stakeSlash.fetch(stakeSlash);
+    expect(slash.amount.toNumber()).to.equal(40);
+    expect(slash.reasonCode).to.equal(7);
+    let state = await program.account.campaign.fetch(campaign.campaign);
+    expect(state.totalStaked.toNumber()).to.equal(60);
+    expect(state.pendingSlashes.toNumber()).to.equal(40);
+    await expectError(closeCampaign(setup, campaign), "SlashPending");
+
+    const finalize = () => finalizeSlash(campaign, user.publicKey, setup.oracle.publicKey);
+    const restore = () => restoreStake(setup, campaign, user.publicKey, setup.oracle.publicKey);
+
+    await expectError(finalize(), "SlashDisputeOpen");
+    await restore();
+    expect(await program.account.stakeSlash.fetchNullable(stakeSlash)).to.equal(null);
+    state = await program.account.campaign.fetch(campaign.campaign);
+    expect(state.totalStaked.toNumber()).to.equal(100);
+    expect(state.pendingSlashes.toNumber()).to.equal(0);
+
+    // an undisputed slash moves the tokens into the reward pool
+    await slashStake(setup, campaign, user.publicKey, setup.oracle, 40, { rewardPool: {} });
+    const { disputeEndsAt } = await program.account.stakeSlash.fetch(stakeSlash);
+    await waitUntil(disputeEndsAt.toNumber());
+    await expectError(restore(), "SlashDisputeClosed");
+    await finalize();
+    const after = await program.account.campaign.fetch(campaign.campaign);
+    expect(after.rewardsAvailable.toNumber() - state.rewardsAvailable.toNumber()).to.equal(40);
+    expect(after.totalStaked.toNumber()).to.equal(60);
+    expect(after.pendingSlashes.toNumber()).to.equal(0);
+    expect(await tokenBalance(campaign.gameDepositVault!)).to.equal(60);
+    const { stakeInfo } = await program.account.campaignPlayer.fetch(campaignPlayer);
+    expect(stakeInfo!.amount.toNumber()).to.equal(60);
+  });
+});
@@ -0,0 +1,159 @@
+import { BN } from "@coral-xyz/anchor";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Keypair } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  CampaignSetup,
+  ata,
+  claimStake,
+  claimStakeYield,
+  closeCampaign,
+  createCampaign,
+  createHouse,
+  endGame,
+  expectError,
+  fundedAccount,
+  newMint,
+  newUser,
+  payingAccounts,
+  playerPda,
+  program,
+  startGame,
+  tokenBalance,
+  tokenConfig,
+  unstakeEarly,
+  waitUntil,
+  wallet,
+} from "./helpers";
+
+describe("stake", () => {
+  it("tracks stakes and returns them to their owner after the campaign closed", async () => {
+    const setup = await createHouse();
+    const gameMint = await newMint();
+    const campaign = await createCampaign(setup, { gameMint, tokenConfig: tokenConfig(gameMint, 10, { stake: {} }), durationSeconds: 8 });
+    const user = await newUser();
+    const deposit = await fundedAccount(gameMint, user, 100);
+
+    await startGame(setup, campaign, { user, accounts: payingAccounts(campaign, deposit) });
+    await endGame(setup, campaign, { user, amountWon: 0 });
+    await startGame(setup, campaign, { user, accounts: payingAccounts(campaign, deposit) });
+    await endGame(setup, campaign, { user, amountWon: 0 });
+    const state = await program.account.campaign.fetch(campaign.campaign);
+    expect(state.totalStaked.toNumber()).to.equal(20);
+    expect(state.stakerCount).to.equal(1);
+    expect(await tokenBalance(campaign.gameDepositVault!)).to.equal(20);
+
+    await expectError(claimStake(campaign, user), "ActiveCampaign");
+    await waitUntil(campaign.endTime);
+
+    // the stake stays escrowed under campaign_auth once the campaign is gone
+    await closeCampaign(setup, campaign);
+    expect(await program.account.campaign.fetchNullable(campaign.campaign)).to.equal(null);
+    expect(await tokenBalance(campaign.gameDepositVault!)).to.equal(20);
+
+    // the player account is derived from the signer, nobody else can collect it
+    const thief = await newUser();
+    await expectError(claimStake(campaign, thief, { campaignPlayer: playerPda(campaign.campaign, user.publicKey) }), "ConstraintSeeds");
+
+    await claimStake(campaign, user);
+    expect(await tokenBalance(campaign.gameDepositVault!)).to.equal(0);
+    expect(await tokenBalance(deposit)).to.equal(80);
+    expect(await tokenBalance(ata(gameMint, user.publicKey))).to.equal(20);
+  });
+  it("returns part of a stake early minus the penalty", async () => {
+    const setup = await createHouse();
+    const gameMint = await newMint();
+    const stake = async (campaign: CampaignSetup, user: Keypair) => {
+      const deposit = await fundedAccount(gameMint, user, 100);
+      await startGame(setup, campaign, { user, accounts: payingAccounts(campaign, deposit) });
+      return deposit;
+    };
+
+    const locked = await createCampaign(setup, { gameMint, tokenConfig: tokenConfig(gameMint, 100, { stake: {} }) });
+    const lockedUser = await newUser();
+    await stake(locked, lockedUser);
+    await endGame(setup, locked, { user: lockedUser, amountWon: 0 });
+    await expectError(unstakeEarly(locked, lockedUser, 10), "EarlyUnstakeDisabled");
+
+    // the penalty of a reward pool campaign funds further games, so it must pay rewards in the staked mint
+    const earlyUnstake = { penaltyBps: 1_000, penaltyDestination: { rewardPool: {} } };
+    await expectError(createCampaign(setup, { gameMint, tokenConfig: tokenConfig(gameMint, 100, { stake: {} }, { earlyUnstake }) }), "InvalidInput");
+    const campaign = await createCampaign(setup, {
+      gameMint,
+      rewardMint: gameMint,
+      tokenConfig: tokenConfig(gameMint, 100, { stake: {} }, { earlyUnstake }),
+    });
+    const user = await newUser();
+    await stake(campaign, user);
+    await expectError(unstakeEarly(campaign, user, 50), "PlayerInGame");
+    await endGame(setup, campaign, { user, amountWon: 0 });
+    const before = await program.account.campaign.fetch(campaign.campaign);
+
+    await expectError(unstakeEarly(campaign, user, 101), "AmountTooHigh");
+    await expectError(unstakeEarly(campaign, user, 50), "InvalidInput");
+    await unstakeEarly(campaign, user, 50, { rewardVault: campaign.rewardVault });
+    expect(await tokenBalance(ata(gameMint, user.publicKey))).to.equal(45);
+    const after = await program.account.campaign.fetch(campaign.campaign);
+    expect(after.rewardsAvailable.toNumber() - before.rewardsAvailable.toNumber()).to.equal(5);
+    expect(after.totalStaked.toNumber()).to.equal(50);
+    expect(after.stakerCount).to.equal(1);
+
+    // the penalty rounds up, splitting an unstake does not avoid it
+    await unstakeEarly(campaign, user, 45, { rewardVault: campaign.rewardVault });
+    await unstakeEarly(campaign, user, 5, { rewardVault: campaign.rewardVault });
+    expect(await tokenBalance(ata(gameMint, user.publicKey))).to.equal(45 + 40 + 4);
+    const emptied = await program.account.campaign.fetch(campaign.campaign);
+    expect(emptied.totalStaked.toNumber()).to.equal(0);
+    expect(emptied.stakerCount).to.equal(0);
+    expect(await tokenBalance(campaign.gameDepositVault!)).to.equal(0);
+  });
+  it("pays stake yield from the reward pool and keeps it from the creator", async () => {
+    const setup = await createHouse();
+    const gameMint = await newMint();
+    const stakeYield = { allocation: new BN(1_000), rewardsPerSecond: new BN(10) };
+    const campaign = await createCampaign(setup, {
+      gameMint,
+      tokenConfig: tokenConfig(gameMint, 10, { stake: {} }, { stakeYield }),
+      durationSeconds: 8,
+    });
+    const user = await newUser();
+    const deposit = await fundedAccount(gameMint, user, 100);
+    const rewardMint = campaign.rewardMint!;
+    const yieldAccount = ata(rewardMint, user.publicKey);
+    const yieldAccounts = {
+      rewardVault: campaign.rewardVault,
+      rewardMint,
+      yieldRecipientAccount: yieldAccount,
+      rewardTokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const created = await program.account.campaign.fetch(campaign.campaign);
+    expect(created.stakeYieldRemaining.toNumber()).to.equal(1_000);
+    expect(created.rewardsAvailable.toNumber()).to.equal(1_000_000 - 1_000);
+
+    await startGame(setup, campaign, { user, accounts: payingAccounts(campaign, deposit) });
+    await endGame(setup, campaign, { user, amountWon: 0 });
+    const { stakeInfo } = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, user.publicKey));
+    await waitUntil(stakeInfo!.stakedSince.toNumber() + 2);
+    await claimStakeYield(campaign, user);
+    const claimedEarly = await tokenBalance(yieldAccount);
+    expect(claimedEarly).to.be.greaterThan(0);
+
+    // the creator can not take back the reward vault while a staker is owed yield
+    await waitUntil(campaign.endTime);
+    await expectError(closeCampaign(setup, campaign), "StakeYieldOwed");
+    await expectError(claimStake(campaign, user), "StakeYieldUnclaimed");
+
+    // the only staker earns everything emitted between its deposit and the campaign end
+    const totalYield = (campaign.endTime - stakeInfo!.stakedSince.toNumber()) * 10;
+    await claimStake(campaign, user, yieldAccounts);
+    expect(await tokenBalance(yieldAccount)).to.equal(totalYield);
+    expect(await tokenBalance(ata(gameMint, user.publicKey))).to.equal(10);
+    const settled = await program.account.campaign.fetch(campaign.campaign);
+    expect(settled.totalStaked.toNumber()).to.equal(0);
+    expect(settled.stakeYieldRemaining.toNumber()).to.equal(1_000 - totalYield);
+
+    // the yield nobody earned goes back with the pool
+    await closeCampaign(setup, campaign);
+    expect(await tokenBalance(ata(rewardMint, wallet.publicKey))).to.equal(1_000_000 - totalYield);
+  });
+});
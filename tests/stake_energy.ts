@@ -0,0 +1,70 @@
+import { BN } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import {
+  createCampaign,
+  createHouse,
+  endGame,
+  expectError,
+  fundedAccount,
+  newMint,
+  newUser,
+  now,
+  playerPda,
+  program,
+  stakeTokens,
+  startGame,
+  tokenBalance,
+  tokenConfig,
+  waitUntil,
+} from "./helpers";
+
+describe("stake for energy", () => {
+  it("gives stakers the energy of the highest tier their stake reaches", async () => {
+    const setup = await createHouse();
+    const gameMint = await newMint();
+    const energy = (maxPlayerEnergy: number) => ({
+      maxPlayerEnergy,
+      energyRechargeMinutes: null,
+      energyReset: { periodMinutes: 1, epoch: 0 },
+    });
+    const campaign = await createCampaign(setup, {
+      gameMint,
+      tokenConfig: tokenConfig(gameMint, 0, { stakeForEnergy: {} }),
+      stakeTiers: [
+        { minAmount: new BN(10), minStakedMinutes: new BN(0), energy: energy(1) },
+        { minAmount: new BN(50), minStakedMinutes: new BN(0), energy: energy(2) },
+      ],
+    });
+    const user = await newUser();
+    const deposit = await fundedAccount(gameMint, user, 100);
+    const game = { user };
+    const player = () => program.account.campaignPlayer.fetch(playerPda(campaign.campaign, user.publicKey));
+
+    // games cost no tokens, without a stake there is no energy
+    await expectError(startGame(setup, campaign, game), "OutOfEnergy");
+
+    await stakeTokens(setup, campaign, user, deposit, 10);
+    await startGame(setup, campaign, game);
+    expect((await player()).energy).to.equal(0);
+    await endGame(setup, campaign, { ...game, amountWon: 0 });
+    await expectError(startGame(setup, campaign, game), "OutOfEnergy");
+
+    await stakeTokens(setup, campaign, user, deposit, 40);
+    const state = await program.account.campaign.fetch(campaign.campaign);
+    expect(state.totalStaked.toNumber()).to.equal(50);
+    expect(state.stakerCount).to.equal(1);
+    expect(await tokenBalance(campaign.gameDepositVault!)).to.equal(50);
+    expect(await tokenBalance(deposit)).to.equal(50);
+    expect((await player()).stakeInfo!.amount.toNumber()).to.equal(50);
+
+    // the higher tier refills to its own cap at the next reset
+    await waitUntil((Math.floor((await now()) / 60) + 1) * 60 + 1);
+    await startGame(setup, campaign, game);
+    expect((await player()).energy).to.equal(1);
+    await endGame(setup, campaign, { ...game, amountWon: 0 });
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { ...game, amountWon: 0 });
+    await expectError(startGame(setup, campaign, game), "OutOfEnergy");
+    expect(await tokenBalance(deposit)).to.equal(50);
+  });
+});
@@ -0,0 +1,149 @@
+import { Keypair, PublicKey, SystemProgram, Transaction, TransactionInstruction, sendAndConfirmTransaction } from "@solana/web3.js";
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeTransferHookInstruction,
+  getMintLen,
+} from "@solana/spl-token";
+import { expect } from "chai";
+import {
+  ata,
+  claimEnergy,
+  connection,
+  coreAccounts,
+  createCampaign,
+  createCoreAsset,
+  createCoreCollection,
+  createHouse,
+  createToken2022Group,
+  createToken2022Member,
+  endGame,
+  energyMintPda,
+  expectError,
+  fundCampaign,
+  fundedAccount,
+  newUser,
+  nftConfig,
+  playerPda,
+  program,
+  startGame,
+  tokenBalance,
+  wallet,
+} from "./helpers";
+
+/** A Token-2022 mint with one extension, initialized by `extension` ahead of the mint itself */
+const newToken2022Mint = async (extensionType: ExtensionType, extension: (mint: PublicKey) => TransactionInstruction) => {
+  const mint = Keypair.generate();
+  const space = getMintLen([extensionType]);
+  const transaction = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: wallet.publicKey,
+      newAccountPubkey: mint.publicKey,
+      space,
+      lamports: await connection.getMinimumBalanceForRentExemption(space),
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    extension(mint.publicKey),
+    createInitializeMintInstruction(mint.publicKey, 6, wallet.publicKey, null, TOKEN_2022_PROGRAM_ID)
+  );
+  await sendAndConfirmTransaction(connection, transaction, [wallet, mint]);
+  return mint.publicKey;
+};
+
+/** Withholds `feeBps` of every transfer */
+const newFeeMint = (feeBps: number) =>
+  newToken2022Mint(ExtensionType.TransferFeeConfig, (mint) =>
+    createInitializeTransferFeeConfigInstruction(mint, wallet.publicKey, wallet.publicKey, feeBps, BigInt(1_000_000), TOKEN_2022_PROGRAM_ID)
+  );
+
+/** Carries the transfer hook extension, `hookProgram` left unset until the issuer enables it */
+const newHookMint = (hookProgram = PublicKey.default) =>
+  newToken2022Mint(ExtensionType.TransferHook, (mint) =>
+    createInitializeTransferHookInstruction(mint, wallet.publicKey, hookProgram, TOKEN_2022_PROGRAM_ID)
+  );
+
+describe("token 2022", () => {
+  it("funds and pays out rewards of a transfer fee mint", async () => {
+    const setup = await createHouse();
+    const collection = await createCoreCollection();
+    const rewardMint = await newFeeMint(100);
+    const campaign = await createCampaign(setup, {
+      rewardMint,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      fund: 100_000,
+      nftConfig: nftConfig(collection),
+    });
+    // the vault only counts what arrived after the 1% fee
+    expect(await tokenBalance(campaign.rewardVault!, TOKEN_2022_PROGRAM_ID)).to.equal(99_000);
+    expect((await program.account.campaign.fetch(campaign.campaign)).rewardsAvailable.toNumber()).to.equal(99_000);
+
+    const user = await newUser();
+    const asset = await createCoreAsset(collection, user.publicKey);
+    const game = { user, identity: asset, accounts: coreAccounts(asset, collection), tokenProgram: TOKEN_2022_PROGRAM_ID };
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { ...game, amountWon: 100 });
+
+    expect(await tokenBalance(ata(rewardMint, user.publicKey, TOKEN_2022_PROGRAM_ID), TOKEN_2022_PROGRAM_ID)).to.equal(99);
+    const player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, asset));
+    expect(player.rewardsClaimed.toNumber()).to.equal(99);
+  });
+
+  it("funds and pays out rewards of a transfer hook mint, forwarding the extra accounts", async () => {
+    const setup = await createHouse();
+    const collection = await createCoreCollection();
+    const rewardMint = await newHookMint();
+    const campaign = await createCampaign(setup, {
+      rewardMint,
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      fund: 1_000,
+      nftConfig: nftConfig(collection),
+    });
+
+    // extra accounts after the named ones reach the transfer CPI untouched
+    const extraAccounts = [{ pubkey: SystemProgram.programId, isSigner: false, isWritable: false }];
+    const funderTokenAccount = await fundedAccount(rewardMint, wallet, 500, TOKEN_2022_PROGRAM_ID);
+    await fundCampaign(campaign, 500, funderTokenAccount, TOKEN_2022_PROGRAM_ID, extraAccounts);
+    expect(await tokenBalance(campaign.rewardVault!, TOKEN_2022_PROGRAM_ID)).to.equal(1_500);
+
+    const user = await newUser();
+    const asset = await createCoreAsset(collection, user.publicKey);
+    const game = { user, identity: asset, accounts: coreAccounts(asset, collection), tokenProgram: TOKEN_2022_PROGRAM_ID };
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { ...game, amountWon: 60, remainingAccounts: extraAccounts });
+    expect(await tokenBalance(ata(rewardMint, user.publicKey, TOKEN_2022_PROGRAM_ID), TOKEN_2022_PROGRAM_ID)).to.equal(60);
+  });
+
+  it("plays and claims energy with a Token-2022 group member NFT", async () => {
+    const setup = await createHouse();
+    const group = await createToken2022Group();
+    const user = await newUser();
+    const member = await createToken2022Member(group, user.publicKey);
+    const memberAccounts = { playerMemberMint: member.mint, playerNftTokenAccount: member.tokenAccount };
+    const game = { user, identity: member.mint, accounts: memberAccounts };
+
+    const closed = await createCampaign(setup, { nftConfig: nftConfig(group) });
+    await expectError(startGame(setup, closed, game), "IdentityTypeNotAllowed");
+
+    const campaign = await createCampaign(setup, { nftConfig: nftConfig(group, { allowToken2022Nfts: true }) });
+    await startGame(setup, campaign, game);
+    await endGame(setup, campaign, { ...game, amountWon: 0 });
+    const player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, member.mint));
+    expect(player.playerIdentity.identityType).to.deep.equal({ token2022Nft: {} });
+
+    const outsider = await createToken2022Member(await createToken2022Group(), user.publicKey);
+    await expectError(
+      startGame(setup, campaign, { user, identity: outsider.mint, accounts: { playerMemberMint: outsider.mint, playerNftTokenAccount: outsider.tokenAccount } }),
+      "CollectionKeyMismatch"
+    );
+
+    // tokenized energy is claimed against the member mint
+    const tokenized = await createCampaign(setup, { nftConfig: nftConfig(group, { tokenizedEnergy: true, allowToken2022Nfts: true }) });
+    const energyMint = energyMintPda(tokenized.campaign);
+    const claim = () => claimEnergy(setup, tokenized, game);
+    await claim();
+    expect(await tokenBalance(ata(energyMint, user.publicKey))).to.equal(3);
+    await expectError(claim(), "OutOfEnergy");
+  });
+});
@@ -0,0 +1,70 @@
+import { getOrCreateAssociatedTokenAccount, transfer } from "@solana/spl-token";
+import { expect } from "chai";
+import {
+  ata,
+  claimEnergy,
+  connection,
+  coreAccounts,
+  createCampaign,
+  createCoreAsset,
+  createCoreCollection,
+  createHouse,
+  createMetadataCollection,
+  createMetadataNft,
+  endGame,
+  energyMintPda,
+  expectError,
+  newUser,
+  nftConfig,
+  startGame,
+  tokenBalance,
+  wallet,
+} from "./helpers";
+
+describe("tokenized energy", () => {
+  it("mints energy to NFT holders and burns it from whoever plays", async () => {
+    const setup = await createHouse();
+    const collection = await createCoreCollection();
+    const campaign = await createCampaign(setup, {
+      nftConfig: nftConfig(collection, { maxPlayerEnergy: 3, tokenizedEnergy: true }),
+    });
+    const energyMint = energyMintPda(campaign.campaign);
+    const holder = await newUser();
+    const asset = await createCoreAsset(collection, holder.publicKey);
+
+    const claim = () => claimEnergy(setup, campaign, { user: holder, identity: asset, accounts: coreAccounts(asset, collection) });
+
+    await claim();
+    const holderEnergy = ata(energyMint, holder.publicKey);
+    expect(await tokenBalance(holderEnergy)).to.equal(3);
+    await expectError(claim(), "OutOfEnergy");
+
+    // energy tokens can be gifted, the receiver plays without holding the NFT
+    const friend = await newUser();
+    const friendEnergy = await getOrCreateAssociatedTokenAccount(connection, wallet, energyMint, friend.publicKey);
+    await transfer(connection, wallet, holderEnergy, friendEnergy.address, holder, 1);
+
+    const game = { user: friend, accounts: { energyMint, playerEnergyAccount: friendEnergy.address } };
+    await startGame(setup, campaign, game);
+    expect(await tokenBalance(friendEnergy.address)).to.equal(0);
+    await endGame(setup, campaign, { user: friend, amountWon: 5 });
+    await expectError(startGame(setup, campaign, game), "custom program error: 0x1");
+  });
+
+  it("only mints the energy of a Token Metadata NFT to its holder", async () => {
+    const setup = await createHouse();
+    const collection = await createMetadataCollection();
+    const campaign = await createCampaign(setup, { nftConfig: nftConfig(collection, { tokenizedEnergy: true }) });
+    const holder = await newUser();
+    const nft = await createMetadataNft(holder.publicKey, { collection });
+    const thief = await newUser();
+
+    // the metadata alone proves nothing, the holder is only checked through the token account
+    await expectError(claimEnergy(setup, campaign, { user: thief, identity: nft.mint, accounts: { playerNftMetadata: nft.metadata } }), "InvalidInput");
+    const nftAccounts = { playerNftMetadata: nft.metadata, playerNftTokenAccount: nft.tokenAccount };
+    await expectError(claimEnergy(setup, campaign, { user: thief, identity: nft.mint, accounts: nftAccounts }), "TokenOwnerMismatch");
+
+    await claimEnergy(setup, campaign, { user: holder, identity: nft.mint, accounts: nftAccounts });
+    expect(await tokenBalance(ata(energyMintPda(campaign.campaign), holder.publicKey))).to.equal(3);
+  });
+});
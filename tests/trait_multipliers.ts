@@ -0,0 +1,102 @@
+import { keccak_256 } from "@noble/hashes/sha3";
+import { PublicKey } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  coreAccounts,
+  createCampaign,
+  createCoreAsset,
+  createCoreCollection,
+  createHouse,
+  createMetadataNft,
+  endGame,
+  expectError,
+  metadataAccounts,
+  newUser,
+  nftConfig,
+  playerPda,
+  program,
+  startGame,
+  wallet,
+} from "./helpers";
+
+type Trait = { key: string; value: string };
+
+const borshString = (text: string) => {
+  const bytes = Buffer.from(text);
+  const len = Buffer.alloc(4);
+  len.writeUInt32LE(bytes.length);
+  return Buffer.concat([len, bytes]);
+};
+
+/** keccak(nft || borsh(traits)), the leaf TraitProof::verify rebuilds */
+const traitLeaf = (nft: PublicKey, traits: Trait[]) => {
+  const len = Buffer.alloc(4);
+  len.writeUInt32LE(traits.length);
+  const encoded = traits.map((t) => Buffer.concat([borshString(t.key), borshString(t.value)]));
+  return Buffer.from(keccak_256(Buffer.concat([nft.toBuffer(), len, ...encoded])));
+};
+
+/** Pairs are hashed in sorted order */
+const hashPair = (a: Buffer, b: Buffer) => Buffer.from(keccak_256(Buffer.concat(Buffer.compare(a, b) <= 0 ? [a, b] : [b, a])));
+
+const multiplier = (value: string, target: any, multiplierBps: number) => ({ traitType: "rarity", value, target, multiplierBps });
+
+describe("trait multipliers", () => {
+  const traitMultipliers = [multiplier("legendary", { rewards: {} }, 15_000), multiplier("legendary", { maxEnergy: {} }, 20_000)];
+
+  it("scales rewards of Core assets carrying the trait in their Attributes plugin", async () => {
+    const setup = await createHouse();
+    const collection = await createCoreCollection();
+    const campaign = await createCampaign(setup, { nftConfig: nftConfig(collection), maxRewardsPerGame: 100, traitMultipliers });
+    const user = await newUser();
+    const legendary = await createCoreAsset(collection, user.publicKey, [
+      { type: "Attributes", attributeList: [{ key: "rarity", value: "legendary" }] },
+    ]);
+    const plain = await createCoreAsset(collection, user.publicKey);
+
+    const game = { user, identity: legendary, accounts: coreAccounts(legendary, collection) };
+    await startGame(setup, campaign, game);
+    const player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, legendary));
+    expect(player.gameMaxRewards.toNumber()).to.equal(150);
+    expect(player.traitBonus).to.deep.equal({ rewardsBps: 15_000, maxEnergyBps: 20_000, rechargeBps: 10_000 });
+    await endGame(setup, campaign, { ...game, amountWon: 150 });
+
+    await startGame(setup, campaign, { user, identity: plain, accounts: coreAccounts(plain, collection) });
+    const plainPlayer = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, plain));
+    expect(plainPlayer.gameMaxRewards.toNumber()).to.equal(100);
+  });
+
+  it("scales rewards of Token Metadata NFTs proving their traits against the trait root", async () => {
+    const setup = await createHouse();
+    const user = await newUser();
+    const legendary = await createMetadataNft(user.publicKey);
+    const common = await createMetadataNft(user.publicKey);
+    const legendaryTraits = [{ key: "rarity", value: "legendary" }];
+    const commonTraits = [{ key: "rarity", value: "common" }];
+    const legendaryLeaf = traitLeaf(legendary.mint, legendaryTraits);
+    const commonLeaf = traitLeaf(common.mint, commonTraits);
+    const traitRoot = [...hashPair(legendaryLeaf, commonLeaf)];
+
+    const campaign = await createCampaign(setup, {
+      nftConfig: nftConfig(wallet.publicKey, { collectionVerification: { creator: {} }, traitRoot }),
+      maxRewardsPerGame: 100,
+      traitMultipliers,
+    });
+    const game = { user, identity: legendary.mint, accounts: metadataAccounts(legendary) };
+
+    // claiming traits the root does not hold
+    await expectError(
+      startGame(setup, campaign, { ...game, traitProof: { traits: legendaryTraits, proof: [[...legendaryLeaf]] } }),
+      "TraitProofInvalid"
+    );
+    await startGame(setup, campaign, { ...game, traitProof: { traits: legendaryTraits, proof: [[...commonLeaf]] } });
+    const player = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, legendary.mint));
+    expect(player.gameMaxRewards.toNumber()).to.equal(150);
+    await endGame(setup, campaign, { ...game, amountWon: 0 });
+
+    const commonGame = { user, identity: common.mint, accounts: metadataAccounts(common) };
+    await startGame(setup, campaign, { ...commonGame, traitProof: { traits: commonTraits, proof: [[...legendaryLeaf]] } });
+    const commonPlayer = await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, common.mint));
+    expect(commonPlayer.gameMaxRewards.toNumber()).to.equal(100);
+  });
+});
@@ -0,0 +1,56 @@
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { expect } from "chai";
+import {
+  CampaignSetup,
+  coreAccounts,
+  createCampaign,
+  createCoreAsset,
+  createCoreCollection,
+  createHouse,
+  endGame,
+  expectError,
+  newUser,
+  nftConfig,
+  playerPda,
+  program,
+  startGame,
+  transferEnergy,
+} from "./helpers";
+
+describe("transfer energy", () => {
+  it("gifts energy within the daily limit and the receiver cap", async () => {
+    const setup = await createHouse();
+    const collection = await createCoreCollection();
+    const campaign = await createCampaign(setup, {
+      nftConfig: nftConfig(collection, { maxPlayerEnergy: 3, maxEnergyTransferPerDay: 2 }),
+    });
+    const players = [];
+    for (let i = 0; i < 2; i++) {
+      const user = await newUser();
+      const asset = await createCoreAsset(collection, user.publicKey);
+      const game = { user, identity: asset, accounts: coreAccounts(asset, collection) };
+      await startGame(setup, campaign, game);
+      await endGame(setup, campaign, { ...game, amountWon: 0 });
+      players.push({ user, asset });
+    }
+    const [alice, bob] = players;
+
+    const transfer = (from: { user: Keypair; asset: PublicKey }, to: { asset: PublicKey }, amount: number) =>
+      transferEnergy(campaign, from.user, from.asset, to.asset, amount, { playerCoreNft: from.asset });
+    const energy = async (campaign: CampaignSetup, asset: PublicKey) =>
+      (await program.account.campaignPlayer.fetch(playerPda(campaign.campaign, asset))).energy;
+
+    await transfer(alice, bob, 1);
+    expect(await energy(campaign, alice.asset)).to.equal(1);
+    expect(await energy(campaign, bob.asset)).to.equal(3);
+
+    await expectError(transfer(alice, bob, 1), "AmountTooHigh");
+    // only the holder of the source NFT can give its energy away
+    await expectError(transfer({ user: bob.user, asset: alice.asset }, bob, 1), "TokenOwnerMismatch");
+
+    await transfer(bob, alice, 1);
+    await expectError(transfer(bob, alice, 2), "EnergyTransferLimitExceeded");
+    expect(await energy(campaign, alice.asset)).to.equal(2);
+    expect(await energy(campaign, bob.asset)).to.equal(2);
+  });
+});